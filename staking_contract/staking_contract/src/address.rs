@@ -1,4 +1,5 @@
-//! Implementation of an `Address` which refers either an account hash, or a contract hash.
+//! Implementation of an `Address` which refers either an account hash, or a contract package
+//! hash.
 use alloc::{
     string::{String, ToString},
     vec::Vec,
@@ -6,19 +7,21 @@ use alloc::{
 use casper_types::{
     account::AccountHash,
     bytesrepr::{self, FromBytes, ToBytes},
-    CLType, CLTyped, ContractHash, ContractPackageHash, Key,
+    CLType, CLTyped, ContractPackageHash, Key,
 };
 use core::convert::TryInto;
 
 /// An enum representing an [`AccountHash`] or a [`ContractPackageHash`].
+///
+/// Both are written as a [`Key`], so token contracts and clients read them as such. A stored
+/// contract is addressed by its package hash, which is what it is identified by when it calls
+/// this contract, and what stays the same across its versions.
 #[derive(PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum Address {
     /// Represents an account hash.
     Account(AccountHash),
     /// Represents a contract package hash.
     ContractPackage(ContractPackageHash),
-
-    ContractHash(ContractHash),
 }
 
 impl Address {
@@ -60,7 +63,6 @@ impl From<Address> for Key {
             Address::ContractPackage(contract_package_hash) => {
                 Key::Hash(contract_package_hash.value())
             }
-            Address::ContractHash(contract_hash) => Key::Hash(contract_hash.value()),
         }
     }
 }
@@ -102,12 +104,11 @@ impl TryInto<String> for Address {
     type Error = crate::error::Error;
 
     fn try_into(self) -> Result<String, Self::Error> {
-        if let Some(account_hash) = self.as_account_hash() {
-            Ok(account_hash.to_string())
-        } else if let Some(contract_package_hash) = self.as_contract_package_hash() {
-            Ok(contract_package_hash.to_string())
-        } else {
-            Err(Self::Error::NeitherAccountHashNorNeitherContractPackageHash)
+        match self {
+            Address::Account(account_hash) => Ok(account_hash.to_string()),
+            Address::ContractPackage(contract_package_hash) => {
+                Ok(contract_package_hash.to_string())
+            }
         }
     }
 }
@@ -0,0 +1,396 @@
+#![no_std]
+#![no_main]
+
+#[cfg(not(target_arch = "wasm32"))]
+compile_error!("target arch should be wasm32: compile with '--target wasm32-unknown-unknown'");
+
+// External dependencies
+extern crate alloc;
+
+use alloc::vec;
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+};
+
+// Contract API dependencies
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    runtime_args, ApiError, CLTyped, CLValue, ContractPackageHash, EntryPoint, EntryPointAccess,
+    EntryPointType, EntryPoints, Group, Key, Parameter, RuntimeArgs, URef, U256,
+};
+
+// Custom dependencies
+use contract_utils::{AdminControl, ContractContext, OnChainContractStorage};
+use staking_contract::{error::Error, staking_factory::StakingFactory};
+
+const ENTRY_POINT_CONSTRUCTOR: &str = "constructor";
+const ENTRY_POINT_CREATE_POOL: &str = "create_pool";
+const ENTRY_POINT_POOL_COUNT: &str = "pool_count";
+const ENTRY_POINT_POOL: &str = "pool";
+const ENTRY_POINT_STAKE: &str = "stake";
+const ENTRY_POINT_WITHDRAW: &str = "withdraw";
+const ENTRY_POINT_ADD_REWARD: &str = "add_reward";
+const ENTRY_POINT_AMOUNT_STAKED: &str = "amount_staked";
+const ENTRY_POINT_STAKER_REWARD: &str = "staker_reward";
+const ENTRY_POINT_ADD_ADMIN: &str = "add_admin";
+const ENTRY_POINT_DISABLE_ADMIN: &str = "disable_admin";
+
+const POOL_ID: &str = "pool_id";
+const AMOUNT: &str = "amount";
+const STAKER: &str = "staker";
+const ADDRESS: &str = "address";
+const NAME: &str = "name";
+const STAKING_STARTS: &str = "staking_starts";
+const STAKING_ENDS: &str = "staking_ends";
+const WITHDRAW_STARTS: &str = "withdraw_starts";
+const WITHDRAW_ENDS: &str = "withdraw_ends";
+const STAKING_TOTAL: &str = "staking_total";
+const STACKING_CONTRACT_PACKAGE_HASH: &str = "stacking_contract_package_hash";
+const ERC20_CONTRACT_PACKAGE_HASH: &str = "erc20_contract_package_hash";
+const STAKER_ADDRESS: &str = "staker_address";
+const WITHDRAWABLE_AMOUNT: &str = "withdrawable_amount";
+const REWARD_AMOUNT: &str = "reward_amount";
+const CONTRACT_PACKAGE_HASH: &str = "staking_factory_package_hash";
+const CONTRACT_HASH: &str = "staking_factory_contract_hash";
+
+const CONSTRUCTOR_GROUP: &str = "constructor";
+
+/// Struct representing the staking factory contract.
+#[derive(Default)]
+struct Factory(OnChainContractStorage);
+
+impl ContractContext<OnChainContractStorage> for Factory {
+    fn storage(&self) -> &OnChainContractStorage {
+        &self.0
+    }
+}
+
+impl StakingFactory<OnChainContractStorage> for Factory {}
+
+impl AdminControl<OnChainContractStorage> for Factory {
+    const NOT_AN_ADMIN: ApiError = ApiError::User(Error::NotAnAdmin as u16);
+}
+
+impl Factory {
+    /// Constructor for the staking factory contract.
+    fn constructor(&mut self) {
+        StakingFactory::init(self);
+        // The installer becomes the first admin.
+        AdminControl::init(self);
+        let installer = self.get_caller();
+        self.add_admin_without_checked(installer);
+    }
+}
+
+// The constructor function takes the following argument:
+// - stacking_contract_package_hash: The package hash of the factory itself.
+#[no_mangle]
+pub extern "C" fn constructor() {
+    let stacking_contract_package_hash =
+        runtime::get_named_arg::<Key>(STACKING_CONTRACT_PACKAGE_HASH);
+    runtime::put_key(
+        STACKING_CONTRACT_PACKAGE_HASH,
+        stacking_contract_package_hash,
+    );
+    Factory::default().constructor();
+}
+
+// The `create_pool` function takes the following arguments:
+// - name: The name of the pool.
+// - address: The address of the pool.
+// - erc20_contract_package_hash: The package hash of the ERC-20 token staked in the pool.
+// - staking_starts, staking_ends: The staking period of the pool.
+// - withdraw_starts, withdraw_ends: The withdrawal period of the pool.
+// - staking_total: The total number of tokens that can be staked in the pool.
+// The function returns the id of the new pool. Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn create_pool() {
+    let name = runtime::get_named_arg::<String>(NAME);
+    let address = runtime::get_named_arg::<String>(ADDRESS);
+    let erc20_contract_package_hash = runtime::get_named_arg::<Key>(ERC20_CONTRACT_PACKAGE_HASH);
+    let staking_starts: u64 = runtime::get_named_arg::<u64>(STAKING_STARTS);
+    let staking_ends: u64 = runtime::get_named_arg::<u64>(STAKING_ENDS);
+    let withdraw_starts: u64 = runtime::get_named_arg::<u64>(WITHDRAW_STARTS);
+    let withdraw_ends: u64 = runtime::get_named_arg::<u64>(WITHDRAW_ENDS);
+    let staking_total: U256 = runtime::get_named_arg::<U256>(STAKING_TOTAL);
+    let mut factory = Factory::default();
+    factory.assert_caller_is_admin();
+    let ret = factory
+        .create_pool(
+            name,
+            address,
+            erc20_contract_package_hash,
+            staking_starts,
+            staking_ends,
+            withdraw_starts,
+            withdraw_ends,
+            staking_total,
+        )
+        .unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `pool_count` function returns the number of pools. Pool ids run from 0 to pool_count - 1.
+#[no_mangle]
+pub extern "C" fn pool_count() {
+    let ret = Factory::default().pool_count();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `pool` function takes the following argument:
+// - pool_id: The id of the pool.
+// The function returns the fields of the pool.
+#[no_mangle]
+pub extern "C" fn pool() {
+    let pool_id = runtime::get_named_arg::<u64>(POOL_ID);
+    let ret = Factory::default().pool(pool_id).unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `amount_staked` function takes the following arguments:
+// - pool_id: The id of the pool.
+// - staker: The address of the staker.
+// The function returns the number of tokens that the staker has staked in the pool.
+#[no_mangle]
+pub extern "C" fn amount_staked() {
+    let pool_id = runtime::get_named_arg::<u64>(POOL_ID);
+    let staker = runtime::get_named_arg::<Key>(STAKER);
+    let ret = Factory::default()
+        .amount_staked(pool_id, staker)
+        .unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `stake` function takes the following arguments:
+// - pool_id: The id of the pool.
+// - amount: The number of tokens to stake.
+// The function returns the number of tokens that were actually staked.
+#[no_mangle]
+pub extern "C" fn stake() {
+    let pool_id = runtime::get_named_arg::<u64>(POOL_ID);
+    let amount = runtime::get_named_arg::<U256>(AMOUNT);
+    let ret = Factory::default().stake(pool_id, amount).unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `withdraw` function takes the following arguments:
+// - pool_id: The id of the pool.
+// - amount: The number of tokens to withdraw.
+// The function withdraws the specified number of tokens together with the earned reward.
+#[no_mangle]
+pub extern "C" fn withdraw() {
+    let pool_id = runtime::get_named_arg::<u64>(POOL_ID);
+    let amount = runtime::get_named_arg::<U256>(AMOUNT);
+    let ret = Factory::default()
+        .withdraw(pool_id, amount)
+        .unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `add_reward` function takes the following arguments:
+// - pool_id: The id of the pool.
+// - reward_amount: The amount of reward to add.
+// - withdrawable_amount: The amount of reward that is withdrawable early.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn add_reward() {
+    let pool_id = runtime::get_named_arg::<u64>(POOL_ID);
+    let reward_amount = runtime::get_named_arg::<U256>(REWARD_AMOUNT);
+    let withdrawable_amount = runtime::get_named_arg::<U256>(WITHDRAWABLE_AMOUNT);
+    let mut factory = Factory::default();
+    factory.assert_caller_is_admin();
+    let ret = factory
+        .add_reward(pool_id, reward_amount, withdrawable_amount)
+        .unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `staker_reward` function takes the following arguments:
+// - pool_id: The id of the pool.
+// - staker_address: The address of the staker.
+// The function returns the reward the staker would receive when withdrawing now.
+#[no_mangle]
+pub extern "C" fn staker_reward() {
+    let pool_id = runtime::get_named_arg::<u64>(POOL_ID);
+    let staker_address = runtime::get_named_arg::<Key>(STAKER_ADDRESS);
+    let ret = Factory::default()
+        .staker_reward(pool_id, staker_address)
+        .unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `add_admin` function takes the following argument:
+// - address: The account or contract that becomes an admin.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn add_admin() {
+    let address = runtime::get_named_arg::<Key>(ADDRESS);
+    Factory::default().add_admin(address);
+}
+
+// The `disable_admin` function takes the following argument:
+// - address: The account or contract that loses admin rights.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn disable_admin() {
+    let address = runtime::get_named_arg::<Key>(ADDRESS);
+    Factory::default().disable_admin(address);
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let (contract_hash, _) = storage::new_contract(
+        get_entry_points(),
+        None,
+        Some(String::from(CONTRACT_PACKAGE_HASH)),
+        None,
+    );
+
+    let package_hash: ContractPackageHash = ContractPackageHash::new(
+        runtime::get_key(CONTRACT_PACKAGE_HASH)
+            .unwrap_or_revert()
+            .into_hash()
+            .unwrap_or_revert(),
+    );
+
+    let package_hash_key: Key = package_hash.into();
+
+    // Prepare constructor args
+    let constructor_args = runtime_args! {
+        STACKING_CONTRACT_PACKAGE_HASH => package_hash_key,
+    };
+
+    let constructor_access: URef =
+        storage::create_contract_user_group(package_hash, CONSTRUCTOR_GROUP, 1, Default::default())
+            .unwrap_or_revert()
+            .pop()
+            .unwrap_or_revert();
+
+    let _: () = runtime::call_contract(contract_hash, ENTRY_POINT_CONSTRUCTOR, constructor_args);
+
+    let mut urefs = BTreeSet::new();
+    urefs.insert(constructor_access);
+    storage::remove_contract_user_group_urefs(package_hash, CONSTRUCTOR_GROUP, urefs)
+        .unwrap_or_revert();
+
+    runtime::put_key(CONTRACT_HASH, contract_hash.into());
+    runtime::put_key(
+        &format!("{contract_hash}_contract_hash_wrapped"),
+        storage::new_uref(contract_hash).into(),
+    );
+}
+
+fn get_entry_points() -> EntryPoints {
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_CONSTRUCTOR,
+        vec![Parameter::new(
+            STACKING_CONTRACT_PACKAGE_HASH,
+            Key::cl_type(),
+        )],
+        <()>::cl_type(),
+        EntryPointAccess::Groups(vec![Group::new(CONSTRUCTOR_GROUP)]),
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_CREATE_POOL,
+        vec![
+            Parameter::new(NAME, String::cl_type()),
+            Parameter::new(ADDRESS, String::cl_type()),
+            Parameter::new(ERC20_CONTRACT_PACKAGE_HASH, Key::cl_type()),
+            Parameter::new(STAKING_STARTS, u64::cl_type()),
+            Parameter::new(STAKING_ENDS, u64::cl_type()),
+            Parameter::new(WITHDRAW_STARTS, u64::cl_type()),
+            Parameter::new(WITHDRAW_ENDS, u64::cl_type()),
+            Parameter::new(STAKING_TOTAL, U256::cl_type()),
+        ],
+        u64::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_POOL_COUNT,
+        vec![],
+        u64::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_POOL,
+        vec![Parameter::new(POOL_ID, u64::cl_type())],
+        BTreeMap::<String, String>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_AMOUNT_STAKED,
+        vec![
+            Parameter::new(POOL_ID, u64::cl_type()),
+            Parameter::new(STAKER, Key::cl_type()),
+        ],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_STAKE,
+        vec![
+            Parameter::new(POOL_ID, u64::cl_type()),
+            Parameter::new(AMOUNT, U256::cl_type()),
+        ],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_WITHDRAW,
+        vec![
+            Parameter::new(POOL_ID, u64::cl_type()),
+            Parameter::new(AMOUNT, U256::cl_type()),
+        ],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_ADD_REWARD,
+        vec![
+            Parameter::new(POOL_ID, u64::cl_type()),
+            Parameter::new(REWARD_AMOUNT, U256::cl_type()),
+            Parameter::new(WITHDRAWABLE_AMOUNT, U256::cl_type()),
+        ],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_STAKER_REWARD,
+        vec![
+            Parameter::new(POOL_ID, u64::cl_type()),
+            Parameter::new(STAKER_ADDRESS, Key::cl_type()),
+        ],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_ADD_ADMIN,
+        vec![Parameter::new(ADDRESS, Key::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_DISABLE_ADMIN,
+        vec![Parameter::new(ADDRESS, Key::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points
+}
@@ -0,0 +1,129 @@
+#![no_std]
+#![no_main]
+
+#[cfg(not(target_arch = "wasm32"))]
+compile_error!("target arch should be wasm32: compile with '--target wasm32-unknown-unknown'");
+
+// External dependencies
+extern crate alloc;
+
+use alloc::{format, string::String, vec};
+
+// Contract API dependencies
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    runtime_args, CLTyped, CLValue, ContractPackageHash, EntryPoint, EntryPointAccess,
+    EntryPointType, EntryPoints, Key, Parameter, RuntimeArgs, U256,
+};
+
+const ENTRY_POINT_STAKE: &str = "stake";
+const ENTRY_POINT_WITHDRAW: &str = "withdraw";
+const ENTRY_POINT_APPROVE: &str = "approve";
+
+const AMOUNT: &str = "amount";
+const SPENDER: &str = "spender";
+const STACKING_CONTRACT_PACKAGE_HASH: &str = "stacking_contract_package_hash";
+const ERC20_CONTRACT_PACKAGE_HASH: &str = "erc20_contract_package_hash";
+const CONTRACT_PACKAGE_HASH: &str = "staking_proxy_package_hash";
+const CONTRACT_HASH: &str = "staking_proxy_contract_hash";
+
+// A stored contract that stakes its own tokens in a staking contract, the way a vault would.
+
+fn contract_package_hash(key: Key) -> ContractPackageHash {
+    ContractPackageHash::new(key.into_hash().unwrap_or_revert())
+}
+
+// The `stake` function takes the following arguments:
+// - erc20_contract_package_hash: The package hash of the staked token.
+// - stacking_contract_package_hash: The package hash of the staking contract.
+// - amount: The number of tokens of this contract to stake.
+// The function approves the staking contract for the tokens, then stakes them.
+#[no_mangle]
+pub extern "C" fn stake() {
+    let erc20_contract_package_hash = runtime::get_named_arg::<Key>(ERC20_CONTRACT_PACKAGE_HASH);
+    let stacking_contract_package_hash =
+        runtime::get_named_arg::<Key>(STACKING_CONTRACT_PACKAGE_HASH);
+    let amount = runtime::get_named_arg::<U256>(AMOUNT);
+    runtime::call_versioned_contract::<()>(
+        contract_package_hash(erc20_contract_package_hash),
+        None,
+        ENTRY_POINT_APPROVE,
+        runtime_args! {
+            SPENDER => stacking_contract_package_hash,
+            AMOUNT => amount,
+        },
+    );
+    let ret: U256 = runtime::call_versioned_contract(
+        contract_package_hash(stacking_contract_package_hash),
+        None,
+        ENTRY_POINT_STAKE,
+        runtime_args! {
+            AMOUNT => amount,
+        },
+    );
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `withdraw` function takes the following arguments:
+// - stacking_contract_package_hash: The package hash of the staking contract.
+// - amount: The number of tokens to withdraw back to this contract.
+#[no_mangle]
+pub extern "C" fn withdraw() {
+    let stacking_contract_package_hash =
+        runtime::get_named_arg::<Key>(STACKING_CONTRACT_PACKAGE_HASH);
+    let amount = runtime::get_named_arg::<U256>(AMOUNT);
+    let ret: U256 = runtime::call_versioned_contract(
+        contract_package_hash(stacking_contract_package_hash),
+        None,
+        ENTRY_POINT_WITHDRAW,
+        runtime_args! {
+            AMOUNT => amount,
+        },
+    );
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let (contract_hash, _) = storage::new_contract(
+        get_entry_points(),
+        None,
+        Some(String::from(CONTRACT_PACKAGE_HASH)),
+        None,
+    );
+
+    runtime::put_key(CONTRACT_HASH, contract_hash.into());
+    runtime::put_key(
+        &format!("{contract_hash}_contract_hash_wrapped"),
+        storage::new_uref(contract_hash).into(),
+    );
+}
+
+fn get_entry_points() -> EntryPoints {
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_STAKE,
+        vec![
+            Parameter::new(ERC20_CONTRACT_PACKAGE_HASH, Key::cl_type()),
+            Parameter::new(STACKING_CONTRACT_PACKAGE_HASH, Key::cl_type()),
+            Parameter::new(AMOUNT, U256::cl_type()),
+        ],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_WITHDRAW,
+        vec![
+            Parameter::new(STACKING_CONTRACT_PACKAGE_HASH, Key::cl_type()),
+            Parameter::new(AMOUNT, U256::cl_type()),
+        ],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points
+}
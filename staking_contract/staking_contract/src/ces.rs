@@ -0,0 +1,204 @@
+//! Events emitted under the Casper Event Standard (CES).
+//!
+//! A contract registers the schemas of its events once, under the `__events_schema` named key.
+//! Every event is then appended to the `__events` dictionary, keyed by its index, and the
+//! `__events_length` named key counts the events emitted so far. Indexers only have to follow
+//! that dictionary.
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use casper_types::{
+    bytesrepr::{self, Bytes, ToBytes},
+    CLType, CLTyped,
+};
+use contract_utils::{get_key, set_key, ContractStorage, Dict, UnwrapOrRevertIn};
+
+/// Name of the dictionary the events are appended to.
+pub const EVENTS_DICT: &str = "__events";
+/// Name of the key holding the number of events emitted so far.
+pub const EVENTS_LENGTH: &str = "__events_length";
+/// Name of the key holding the schemas of the events.
+pub const EVENTS_SCHEMA: &str = "__events_schema";
+/// Name of the key holding the version of the standard the events follow.
+pub const CES_VERSION_KEY: &str = "__events_ces_version";
+/// Version of the standard the events follow.
+pub const CES_VERSION: &str = "0.1.0";
+
+/// An event of the standard: its name, its schema and its byte representation.
+pub trait EventInstance: ToBytes {
+    /// Returns the name of the event.
+    fn name() -> String;
+
+    /// Returns the schema of the event.
+    fn schema() -> Schema;
+
+    /// Returns the name the serialized event starts with.
+    fn prefixed_name() -> String {
+        format!("event_{}", Self::name())
+    }
+}
+
+/// The names and types of the fields of an event, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema(Vec<(String, CLType)>);
+
+impl Schema {
+    /// Returns an empty schema.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the schema with one more field.
+    pub fn with_elem(mut self, name: &str, cl_type: CLType) -> Self {
+        self.0.push((String::from(name), cl_type));
+        self
+    }
+}
+
+impl ToBytes for Schema {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut result = bytesrepr::allocate_buffer(self)?;
+        result.append(&mut (self.0.len() as u32).to_bytes()?);
+        for (name, cl_type) in &self.0 {
+            result.append(&mut name.to_bytes()?);
+            append_cl_type(cl_type, &mut result)?;
+        }
+        Ok(result)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.iter().fold(
+            bytesrepr::U32_SERIALIZED_LENGTH,
+            |length, (name, cl_type)| length + name.serialized_length() + cl_type_length(cl_type),
+        )
+    }
+}
+
+/// The schemas of all the events a contract emits, by event name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schemas(BTreeMap<String, Schema>);
+
+impl Schemas {
+    /// Returns an empty set of schemas.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the schemas with the one of the given event added.
+    pub fn with<T: EventInstance>(mut self) -> Self {
+        self.0.insert(T::name(), T::schema());
+        self
+    }
+}
+
+impl CLTyped for Schemas {
+    fn cl_type() -> CLType {
+        CLType::Any
+    }
+}
+
+impl ToBytes for Schemas {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.0.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.0.serialized_length()
+    }
+}
+
+/// Registers the schemas of the events and creates the events dictionary.
+///
+/// Must be called once, from the constructor of the contract.
+pub fn init<S: ContractStorage>(storage: &S, schemas: Schemas) {
+    Dict::init(storage, EVENTS_DICT);
+    set_key(storage, EVENTS_LENGTH, 0u32);
+    set_key(storage, EVENTS_SCHEMA, schemas);
+    set_key(storage, CES_VERSION_KEY, String::from(CES_VERSION));
+}
+
+/// Registers the schemas of the events of an upgraded contract, keeping the events emitted so
+/// far.
+pub fn migrate<S: ContractStorage>(storage: &S, schemas: Schemas) {
+    set_key(storage, EVENTS_SCHEMA, schemas);
+}
+
+/// Appends the event to the events dictionary.
+pub fn emit<S: ContractStorage, T: EventInstance>(storage: &S, event: &T) {
+    let events_length: u32 = get_key(storage, EVENTS_LENGTH).unwrap_or_revert_in(storage);
+    let event = Bytes::from(event.to_bytes().unwrap_or_revert_in(storage));
+    Dict::instance(storage, EVENTS_DICT).set(&events_length.to_string(), event);
+    set_key(storage, EVENTS_LENGTH, events_length + 1);
+}
+
+// `CLType` has no public byte representation, so the tags of the standard are written here.
+fn append_cl_type(cl_type: &CLType, stream: &mut Vec<u8>) -> Result<(), bytesrepr::Error> {
+    match cl_type {
+        CLType::Bool => stream.push(0),
+        CLType::I32 => stream.push(1),
+        CLType::I64 => stream.push(2),
+        CLType::U8 => stream.push(3),
+        CLType::U32 => stream.push(4),
+        CLType::U64 => stream.push(5),
+        CLType::U128 => stream.push(6),
+        CLType::U256 => stream.push(7),
+        CLType::U512 => stream.push(8),
+        CLType::Unit => stream.push(9),
+        CLType::String => stream.push(10),
+        CLType::Key => stream.push(11),
+        CLType::URef => stream.push(12),
+        CLType::Option(inner) => {
+            stream.push(13);
+            append_cl_type(inner, stream)?;
+        }
+        CLType::List(inner) => {
+            stream.push(14);
+            append_cl_type(inner, stream)?;
+        }
+        CLType::ByteArray(length) => {
+            stream.push(15);
+            stream.append(&mut length.to_bytes()?);
+        }
+        CLType::Result { ok, err } => {
+            stream.push(16);
+            append_cl_type(ok, stream)?;
+            append_cl_type(err, stream)?;
+        }
+        CLType::Map { key, value } => {
+            stream.push(17);
+            append_cl_type(key, stream)?;
+            append_cl_type(value, stream)?;
+        }
+        CLType::Tuple1(types) => {
+            stream.push(18);
+            types
+                .iter()
+                .try_for_each(|cl_type| append_cl_type(cl_type, stream))?;
+        }
+        CLType::Tuple2(types) => {
+            stream.push(19);
+            types
+                .iter()
+                .try_for_each(|cl_type| append_cl_type(cl_type, stream))?;
+        }
+        CLType::Tuple3(types) => {
+            stream.push(20);
+            types
+                .iter()
+                .try_for_each(|cl_type| append_cl_type(cl_type, stream))?;
+        }
+        CLType::Any => stream.push(21),
+        CLType::PublicKey => stream.push(22),
+    }
+    Ok(())
+}
+
+fn cl_type_length(cl_type: &CLType) -> usize {
+    let mut stream = Vec::new();
+    // writing to a vector never fails
+    append_cl_type(cl_type, &mut stream).unwrap_or_default();
+    stream.len()
+}
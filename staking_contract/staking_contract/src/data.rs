@@ -1,22 +1,39 @@
+use crate::address::Address;
+use crate::ces;
+use crate::epochs::Epoch;
 use crate::error::Error;
-use crate::event::StakingContractEvent;
-use alloc::{
-    collections::BTreeMap,
-    string::{String, ToString},
-    vec::Vec,
+use crate::event::{self, StakingContractEvent};
+use alloc::string::{String, ToString};
+use casper_types::{system::CallStackElement, ContractPackageHash, Key, U256};
+use contract_utils::{
+    get_key, key_and_value_to_str, set_key, ContractStorage, Dict, UnwrapOrRevertIn,
 };
-use casper_contract::{
-    contract_api::{runtime::get_call_stack, storage},
-    unwrap_or_revert::UnwrapOrRevert,
-};
-use casper_types::{system::CallStackElement, ContractPackageHash, Key, URef, U256};
-use contract_utils::{get_key, key_to_str, set_key, Dict};
-use core::convert::TryInto;
+use staking_math::math;
 
 // Dictionary key for storing the amount staked by addresses
 const AMOUNT_STAKED_BY_ADDRESS_DICT: &str = "amount_staked_by_addresses_dict";
-// Dictionary key for storing the contract package hash
-const CONTRACT_PACKAGE_HASH: &str = "contract_package_hash";
+// Dictionary key for storing the addresses that ever staked, by index
+const STAKERS_BY_INDEX_DICT: &str = "stakers_by_index";
+// Dictionary key for storing the weight of the stake of addresses
+const WEIGHT_BY_ADDRESS_DICT: &str = "weight_by_addresses_dict";
+// Dictionary key for storing the time until which the stake of addresses is locked
+const UNLOCK_TIME_BY_ADDRESS_DICT: &str = "unlock_time_by_addresses_dict";
+// Dictionary key for storing the stake positions of addresses
+const STAKE_POSITIONS_DICT: &str = "stake_positions";
+// Dictionary key for storing the number of stake positions of addresses
+const STAKE_POSITION_COUNT_BY_ADDRESS_DICT: &str = "stake_position_count_by_addresses_dict";
+// Dictionary key for storing the first position of addresses that hasn't been fully withdrawn
+const FIRST_OPEN_POSITION_BY_ADDRESS_DICT: &str = "first_open_position_by_addresses_dict";
+// Dictionary key for storing the reward multiplier of every lock duration
+const LOCK_TIERS_DICT: &str = "lock_tiers";
+// Dictionary key for storing the reward claimed by addresses without withdrawing
+const CLAIMED_REWARD_BY_ADDRESS_DICT: &str = "claimed_rewards_by_addresses_dict";
+// Dictionary key for storing the reward debt of addresses in streaming mode
+const REWARD_DEBT_BY_ADDRESS_DICT: &str = "reward_debt_by_addresses_dict";
+// Dictionary key for storing the receipts owners allow spenders to move
+const ALLOWANCES_DICT: &str = "allowances";
+// Dictionary key for storing the addresses allowed to stake in allowlist mode
+const ALLOWLIST_DICT: &str = "allowlist";
 
 // Keys used for accessing contract state
 pub const NAME: &str = "name";
@@ -31,57 +48,123 @@ pub const EARLY_WITHDRAW_REWARD: &str = "early_withdraw_reward";
 pub const STAKED_TOTAL: &str = "staked_total";
 pub const REWARD_BALANCE: &str = "reward_balance";
 pub const STAKED_BALANCE: &str = "staked_balance";
-const AMOUNT: &str = "amount";
-const REWARD: &str = "reward";
-const TOKEN_ADDRESS: &str = "token_address";
-const STAKER_ADDRESS: &str = "staker_address";
-const REWARD_AMOUNT: &str = "reward_amount";
-const STAKED_AMOUNT: &str = "staked_amount";
-const REQUESTED_AMOUNT: &str = "requested_amount";
-const WITHDRAWABLE_AMOUNT: &str = "withdrawable_amount";
-
-const EVENT_TYPE: &str = "event_type";
-
-const STAKE: &str = "stake";
-const PAID_OUT: &str = "paid_out";
-const ADD_REWARD: &str = "add_reward";
-const REFUNDED: &str = "refunded";
+pub const POOL_MODE: &str = "pool_mode";
+pub const REWARD_RATE: &str = "reward_rate";
+pub const ACC_REWARD_PER_SHARE: &str = "acc_reward_per_share";
+pub const LAST_REWARD_TIME: &str = "last_reward_time";
+pub const PAUSED: &str = "paused";
+pub const WEIGHTED_TOTAL: &str = "weighted_staked_total";
+pub const WEIGHTED_BALANCE: &str = "weighted_staked_balance";
+pub const EARLY_WITHDRAW_PENALTY: &str = "early_withdraw_penalty";
+pub const PENALTY_TREASURY: &str = "penalty_treasury";
+pub const SYMBOL: &str = "symbol";
+pub const DECIMALS: &str = "decimals";
+pub const TOTAL_SUPPLY: &str = "total_supply";
+pub const ALLOWLIST_ENABLED: &str = "allowlist_enabled";
+pub const MERKLE_ROOT: &str = "merkle_root";
+pub const MIN_STAKE: &str = "min_stake";
+pub const MAX_STAKE_PER_ADDRESS: &str = "max_stake_per_address";
+pub const STAKER_COUNT: &str = "staker_count";
+pub const SCHEMA_VERSION: &str = "schema_version";
+/// Version of the layout of the state this code reads and writes
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+/// Pool mode where the reward is shared out over the fixed staking and withdraw windows
+pub const POOL_MODE_FIXED_WINDOW: u8 = 0;
+/// Pool mode where the reward streams every second to whoever is staked
+pub const POOL_MODE_STREAMING: u8 = 1;
 
 // Structure for managing staked tokens
-pub struct StakedTokens {
-    addresses_staked_dict: Dict,
+pub struct StakedTokens<'a, S: ContractStorage> {
+    storage: &'a S,
+    addresses_staked_dict: Dict<'a, S>,
+    stakers_dict: Dict<'a, S>,
+    weights_dict: Dict<'a, S>,
+    unlock_times_dict: Dict<'a, S>,
 }
 
-impl StakedTokens {
+impl<'a, S: ContractStorage> StakedTokens<'a, S> {
     /// Creates a new instance of `StakedTokens`
-    pub fn instance() -> StakedTokens {
+    pub fn instance(storage: &'a S) -> StakedTokens<'a, S> {
         StakedTokens {
-            addresses_staked_dict: Dict::instance(AMOUNT_STAKED_BY_ADDRESS_DICT),
+            storage,
+            addresses_staked_dict: Dict::instance(storage, AMOUNT_STAKED_BY_ADDRESS_DICT),
+            stakers_dict: Dict::instance(storage, STAKERS_BY_INDEX_DICT),
+            weights_dict: Dict::instance(storage, WEIGHT_BY_ADDRESS_DICT),
+            unlock_times_dict: Dict::instance(storage, UNLOCK_TIME_BY_ADDRESS_DICT),
         }
     }
 
-    /// Initializes the `StakedTokens` dictionary
-    pub fn init() {
-        Dict::init(AMOUNT_STAKED_BY_ADDRESS_DICT);
+    /// Initializes the `StakedTokens` dictionaries
+    pub fn init(storage: &S) {
+        Dict::init(storage, AMOUNT_STAKED_BY_ADDRESS_DICT);
+        Dict::init(storage, STAKERS_BY_INDEX_DICT);
+        Dict::init(storage, WEIGHT_BY_ADDRESS_DICT);
+        Dict::init(storage, UNLOCK_TIME_BY_ADDRESS_DICT);
+    }
+
+    /// Retrieves the weight of the stake of the given address
+    pub fn get_weight(&self, address: &Key) -> U256 {
+        self.weights_dict.get_by_key(address).unwrap_or_default()
+    }
+
+    /// Sets the weight of the stake of the given address
+    pub fn set_weight(&self, address: &Key, weight: U256) {
+        self.weights_dict.set_by_key(address, weight);
+    }
+
+    /// Retrieves the time until which the stake of the given address is locked
+    pub fn get_unlock_time(&self, address: &Key) -> u64 {
+        self.unlock_times_dict
+            .get_by_key(address)
+            .unwrap_or_default()
+    }
+
+    /// Sets the time until which the stake of the given address is locked
+    pub fn set_unlock_time(&self, address: &Key, unlock_time: u64) {
+        self.unlock_times_dict.set_by_key(address, unlock_time);
     }
 
     /// Retrieves the amount staked by the given address
     pub fn get_amount_staked_by_address(&self, address: &Key) -> Option<U256> {
-        self.addresses_staked_dict.get(&key_to_str(address))
+        self.addresses_staked_dict.get_by_key(address)
+    }
+
+    /// Retrieves the number of addresses that ever staked
+    pub fn staker_count(&self) -> u64 {
+        get_key(self.storage, STAKER_COUNT).unwrap_or_default()
+    }
+
+    /// Retrieves the address that staked in the given order, starting at 0
+    pub fn get_staker(&self, index: u64) -> Option<Key> {
+        self.stakers_dict.get(&index.to_string())
     }
 
-    /// Adds a stake for the owner
-    pub fn add_stake(&self, owner: &Key, amount: &U256) {
+    /// Adds a stake for the owner, minting as many receipts
+    ///
+    /// The first stake of an address also appends it to the stakers.
+    pub fn add_stake(&self, owner: &Key, amount: &U256) -> Result<(), Error> {
         let new_amount = if let Some(staked_amount) = self.get_amount_staked_by_address(owner) {
-            staked_amount + amount
+            math::add(staked_amount, *amount)?
         } else {
+            self.append_staker(owner);
             *amount
         };
-        self.addresses_staked_dict
-            .set(&key_to_str(owner), new_amount);
+        self.addresses_staked_dict.set_by_key(owner, new_amount);
+        set_total_supply(
+            self.storage,
+            math::add(total_supply(self.storage), *amount)?,
+        );
+        Ok(())
+    }
+
+    // Appends the given address to the stakers
+    fn append_staker(&self, owner: &Key) {
+        let staker_count = self.staker_count();
+        self.stakers_dict.set(&staker_count.to_string(), *owner);
+        set_key(self.storage, STAKER_COUNT, staker_count + 1);
     }
 
-    /// Withdraws a stake for the owner
+    /// Withdraws a stake for the owner, burning as many receipts
     pub fn withdraw_stake(&self, owner: &Key, amount: &U256) -> Result<(), Error> {
         let staked_amount = self
             .get_amount_staked_by_address(owner)
@@ -89,136 +172,605 @@ impl StakedTokens {
         let new_amount = staked_amount
             .checked_sub(*amount)
             .ok_or(Error::CheckedSub)?;
-        self.addresses_staked_dict
-            .set(&key_to_str(owner), new_amount);
+        self.addresses_staked_dict.set_by_key(owner, new_amount);
+        set_total_supply(
+            self.storage,
+            math::sub(total_supply(self.storage), *amount)?,
+        );
+        Ok(())
+    }
+}
+
+/// A single deposit of a staker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StakePosition {
+    /// The amount of tokens deposited
+    pub amount: U256,
+    /// The part of the amount that has been withdrawn
+    pub withdrawn: U256,
+}
+
+impl StakePosition {
+    /// Returns the part of the amount that is still staked
+    pub fn remaining(&self) -> Result<U256, Error> {
+        Ok(math::sub(self.amount, self.withdrawn)?)
+    }
+}
+
+impl From<(U256, U256)> for StakePosition {
+    fn from((amount, withdrawn): (U256, U256)) -> Self {
+        StakePosition { amount, withdrawn }
+    }
+}
+
+impl From<StakePosition> for (U256, U256) {
+    fn from(position: StakePosition) -> Self {
+        (position.amount, position.withdrawn)
+    }
+}
+
+// Structure for managing the ledger of stake positions, numbered per staker from 0
+pub struct StakePositions<'a, S: ContractStorage> {
+    storage: &'a S,
+    positions_dict: Dict<'a, S>,
+    counts_dict: Dict<'a, S>,
+    first_open_dict: Dict<'a, S>,
+}
+
+impl<'a, S: ContractStorage> StakePositions<'a, S> {
+    /// Creates a new instance of `StakePositions`
+    pub fn instance(storage: &'a S) -> StakePositions<'a, S> {
+        StakePositions {
+            storage,
+            positions_dict: Dict::instance(storage, STAKE_POSITIONS_DICT),
+            counts_dict: Dict::instance(storage, STAKE_POSITION_COUNT_BY_ADDRESS_DICT),
+            first_open_dict: Dict::instance(storage, FIRST_OPEN_POSITION_BY_ADDRESS_DICT),
+        }
+    }
+
+    /// Initializes the `StakePositions` dictionaries
+    pub fn init(storage: &S) {
+        Dict::init(storage, STAKE_POSITIONS_DICT);
+        Dict::init(storage, STAKE_POSITION_COUNT_BY_ADDRESS_DICT);
+        Dict::init(storage, FIRST_OPEN_POSITION_BY_ADDRESS_DICT);
+    }
+
+    /// Retrieves the number of positions opened by the given address
+    pub fn count(&self, owner: &Key) -> u64 {
+        self.counts_dict.get_by_key(owner).unwrap_or_default()
+    }
+
+    /// Retrieves the given position of the given address
+    pub fn get(&self, owner: &Key, position_id: u64) -> Option<StakePosition> {
+        self.positions_dict
+            .get::<(U256, U256)>(&key_and_value_to_str(self.storage, owner, &position_id))
+            .map(StakePosition::from)
+    }
+
+    fn set(&self, owner: &Key, position_id: u64, position: StakePosition) {
+        self.positions_dict.set(
+            &key_and_value_to_str(self.storage, owner, &position_id),
+            <(U256, U256)>::from(position),
+        );
+    }
+
+    // Retrieves the id of the first position of the given address that hasn't been fully
+    // withdrawn, or the number of positions if there is none
+    fn first_open(&self, owner: &Key) -> u64 {
+        self.first_open_dict.get_by_key(owner).unwrap_or_default()
+    }
+
+    /// Opens a new position for the owner and returns its id
+    pub fn open(&self, owner: &Key, amount: U256) -> u64 {
+        let position_id = self.count(owner);
+        self.set(
+            owner,
+            position_id,
+            StakePosition {
+                amount,
+                withdrawn: U256::zero(),
+            },
+        );
+        self.counts_dict.set_by_key(owner, position_id + 1);
+        position_id
+    }
+
+    /// Withdraws the given amount from the given position of the owner
+    pub fn withdraw(&self, owner: &Key, position_id: u64, amount: U256) -> Result<(), Error> {
+        let mut position = self.get(owner, position_id).ok_or(Error::UnknownPosition)?;
+        if amount > position.remaining()? {
+            return Err(Error::NotRequiredStake);
+        }
+        position.withdrawn = math::add(position.withdrawn, amount)?;
+        self.set(owner, position_id, position);
+        Ok(())
+    }
+
+    /// Withdraws the given amount from the positions of the owner, oldest first
+    ///
+    /// The search starts at the first position that hasn't been fully withdrawn, so the positions
+    /// emptied before are not read again.
+    pub fn withdraw_oldest(&self, owner: &Key, amount: U256) -> Result<(), Error> {
+        let count = self.count(owner);
+        let mut first_open = self.first_open(owner);
+        let mut left = amount;
+        while !left.is_zero() && first_open < count {
+            let mut position = self
+                .get(owner, first_open)
+                .unwrap_or_revert_in(self.storage);
+            let taken = position.remaining()?.min(left);
+            if !taken.is_zero() {
+                position.withdrawn = math::add(position.withdrawn, taken)?;
+                self.set(owner, first_open, position);
+                left = math::sub(left, taken)?;
+            }
+            if position.remaining()?.is_zero() {
+                first_open += 1;
+            }
+        }
+        if !left.is_zero() {
+            return Err(Error::NotRequiredStake);
+        }
+        self.first_open_dict.set_by_key(owner, first_open);
         Ok(())
     }
+
+    /// Moves the given amount from the positions of the owner, oldest first, to a new position
+    /// of the recipient
+    pub fn transfer_oldest(&self, owner: &Key, recipient: &Key, amount: U256) -> Result<(), Error> {
+        self.withdraw_oldest(owner, amount)?;
+        self.open(recipient, amount);
+        Ok(())
+    }
+}
+
+// Structure for managing the reward multiplier of every lock duration
+pub struct LockTiers<'a, S: ContractStorage> {
+    lock_tiers_dict: Dict<'a, S>,
+}
+
+impl<'a, S: ContractStorage> LockTiers<'a, S> {
+    /// Creates a new instance of `LockTiers`
+    pub fn instance(storage: &'a S) -> LockTiers<'a, S> {
+        LockTiers {
+            lock_tiers_dict: Dict::instance(storage, LOCK_TIERS_DICT),
+        }
+    }
+
+    /// Initializes the `LockTiers` dictionary
+    pub fn init(storage: &S) {
+        Dict::init(storage, LOCK_TIERS_DICT);
+    }
+
+    /// Retrieves the reward multiplier, in basis points, of the given lock duration
+    pub fn get_multiplier(&self, lock_duration: u64) -> Option<u32> {
+        self.lock_tiers_dict.get(&lock_duration.to_string())
+    }
+
+    /// Sets the reward multiplier, in basis points, of the given lock duration
+    pub fn set_multiplier(&self, lock_duration: u64, multiplier: u32) {
+        self.lock_tiers_dict
+            .set(&lock_duration.to_string(), multiplier);
+    }
+}
+
+// Structure for managing the reward claimed by stakers who haven't withdrawn yet
+pub struct ClaimedRewards<'a, S: ContractStorage> {
+    claimed_rewards_dict: Dict<'a, S>,
+}
+
+impl<'a, S: ContractStorage> ClaimedRewards<'a, S> {
+    /// Creates a new instance of `ClaimedRewards`
+    pub fn instance(storage: &'a S) -> ClaimedRewards<'a, S> {
+        ClaimedRewards {
+            claimed_rewards_dict: Dict::instance(storage, CLAIMED_REWARD_BY_ADDRESS_DICT),
+        }
+    }
+
+    /// Initializes the `ClaimedRewards` dictionary
+    pub fn init(storage: &S) {
+        Dict::init(storage, CLAIMED_REWARD_BY_ADDRESS_DICT);
+    }
+
+    /// Retrieves the reward claimed by the given address
+    pub fn get_claimed_reward(&self, address: &Key) -> U256 {
+        self.claimed_rewards_dict
+            .get_by_key(address)
+            .unwrap_or_default()
+    }
+
+    /// Sets the reward claimed by the given address
+    pub fn set_claimed_reward(&self, address: &Key, claimed_reward: U256) {
+        self.claimed_rewards_dict
+            .set_by_key(address, claimed_reward);
+    }
+}
+
+// Structure for managing the receipts owners allow spenders to move
+pub struct Allowances<'a, S: ContractStorage> {
+    allowances_dict: Dict<'a, S>,
+}
+
+impl<'a, S: ContractStorage> Allowances<'a, S> {
+    /// Creates a new instance of `Allowances`
+    pub fn instance(storage: &'a S) -> Allowances<'a, S> {
+        Allowances {
+            allowances_dict: Dict::instance(storage, ALLOWANCES_DICT),
+        }
+    }
+
+    /// Initializes the `Allowances` dictionary
+    pub fn init(storage: &S) {
+        Dict::init(storage, ALLOWANCES_DICT);
+    }
+
+    /// Retrieves the receipts the owner allows the spender to move
+    pub fn get_allowance(&self, owner: &Key, spender: &Key) -> U256 {
+        self.allowances_dict
+            .get_by_keys((owner, spender))
+            .unwrap_or_default()
+    }
+
+    /// Sets the receipts the owner allows the spender to move
+    pub fn set_allowance(&self, owner: &Key, spender: &Key, amount: U256) {
+        self.allowances_dict.set_by_keys((owner, spender), amount);
+    }
+}
+
+// Structure for managing the addresses allowed to stake in allowlist mode
+pub struct Allowlist<'a, S: ContractStorage> {
+    allowlist_dict: Dict<'a, S>,
+}
+
+impl<'a, S: ContractStorage> Allowlist<'a, S> {
+    /// Creates a new instance of `Allowlist`
+    pub fn instance(storage: &'a S) -> Allowlist<'a, S> {
+        Allowlist {
+            allowlist_dict: Dict::instance(storage, ALLOWLIST_DICT),
+        }
+    }
+
+    /// Initializes the `Allowlist` dictionary
+    pub fn init(storage: &S) {
+        Dict::init(storage, ALLOWLIST_DICT);
+    }
+
+    /// Retrieves whether the given address is on the allowlist
+    pub fn is_allowed(&self, address: &Key) -> bool {
+        self.allowlist_dict.get_by_key(address).unwrap_or_default()
+    }
+
+    /// Adds the given address to the allowlist, or removes it
+    pub fn set_allowed(&self, address: &Key, allowed: bool) {
+        self.allowlist_dict.set_by_key(address, allowed);
+    }
+}
+
+// Structure for managing the reward debt of stakers in streaming mode
+pub struct RewardDebts<'a, S: ContractStorage> {
+    reward_debts_dict: Dict<'a, S>,
+}
+
+impl<'a, S: ContractStorage> RewardDebts<'a, S> {
+    /// Creates a new instance of `RewardDebts`
+    pub fn instance(storage: &'a S) -> RewardDebts<'a, S> {
+        RewardDebts {
+            reward_debts_dict: Dict::instance(storage, REWARD_DEBT_BY_ADDRESS_DICT),
+        }
+    }
+
+    /// Initializes the `RewardDebts` dictionary
+    pub fn init(storage: &S) {
+        Dict::init(storage, REWARD_DEBT_BY_ADDRESS_DICT);
+    }
+
+    /// Retrieves the reward debt of the given address
+    pub fn get_reward_debt(&self, address: &Key) -> U256 {
+        self.reward_debts_dict
+            .get_by_key(address)
+            .unwrap_or_default()
+    }
+
+    /// Sets the reward debt of the given address
+    pub fn set_reward_debt(&self, address: &Key, reward_debt: U256) {
+        self.reward_debts_dict.set_by_key(address, reward_debt);
+    }
 }
 
 /// Retrieves the stored name
-pub fn name() -> String {
-    get_key(NAME).unwrap_or_revert()
+pub fn name<S: ContractStorage>(storage: &S) -> String {
+    get_key(storage, NAME).unwrap_or_revert_in(storage)
 }
 
 /// Sets the name
-pub fn set_name(name: String) {
-    set_key(NAME, name);
+pub fn set_name<S: ContractStorage>(storage: &S, name: String) {
+    set_key(storage, NAME, name);
 }
 
 /// Retrieves the stored address
-pub fn address() -> String {
-    get_key(ADDRESS).unwrap_or_revert()
+pub fn address<S: ContractStorage>(storage: &S) -> String {
+    get_key(storage, ADDRESS).unwrap_or_revert_in(storage)
 }
 
 /// Sets the address
-pub fn set_address(address: String) {
-    set_key(ADDRESS, address);
+pub fn set_address<S: ContractStorage>(storage: &S, address: String) {
+    set_key(storage, ADDRESS, address);
 }
 
 /// Retrieves the staking start time
-pub fn staking_starts() -> u64 {
-    get_key(STAKING_STARTS).unwrap_or_revert()
+pub fn staking_starts<S: ContractStorage>(storage: &S) -> u64 {
+    Epoch::current(storage).staking_starts()
 }
 
 /// Sets the staking start time
-pub fn set_staking_starts(staking_starts: u64) {
-    set_key(STAKING_STARTS, staking_starts);
+pub fn set_staking_starts<S: ContractStorage>(storage: &S, staking_starts: u64) {
+    Epoch::current(storage).set_staking_starts(staking_starts);
 }
 
 /// Retrieves the staking end time
-pub fn staking_ends() -> u64 {
-    get_key(STAKING_ENDS).unwrap_or_revert()
+pub fn staking_ends<S: ContractStorage>(storage: &S) -> u64 {
+    Epoch::current(storage).staking_ends()
 }
 
 /// Sets the staking end time
-pub fn set_staking_ends(staking_ends: u64) {
-    set_key(STAKING_ENDS, staking_ends);
+pub fn set_staking_ends<S: ContractStorage>(storage: &S, staking_ends: u64) {
+    Epoch::current(storage).set_staking_ends(staking_ends);
 }
 
 /// Retrieves the withdrawal start time
-pub fn withdraw_starts() -> u64 {
-    get_key(WITHDRAW_STARTS).unwrap_or_default()
+pub fn withdraw_starts<S: ContractStorage>(storage: &S) -> u64 {
+    Epoch::current(storage).withdraw_starts()
 }
 
 /// Sets the withdrawal start time
-pub fn set_withdraw_starts(withdraw_starts: u64) {
-    set_key(WITHDRAW_STARTS, withdraw_starts);
+pub fn set_withdraw_starts<S: ContractStorage>(storage: &S, withdraw_starts: u64) {
+    Epoch::current(storage).set_withdraw_starts(withdraw_starts);
 }
 
 /// Retrieves the withdrawal end time
-pub fn withdraw_ends() -> u64 {
-    get_key(WITHDRAW_ENDS).unwrap_or_default()
+pub fn withdraw_ends<S: ContractStorage>(storage: &S) -> u64 {
+    Epoch::current(storage).withdraw_ends()
 }
 
 /// Sets the withdrawal end time
-pub fn set_withdraw_ends(withdraw_ends: u64) {
-    set_key(WITHDRAW_ENDS, withdraw_ends);
+pub fn set_withdraw_ends<S: ContractStorage>(storage: &S, withdraw_ends: u64) {
+    Epoch::current(storage).set_withdraw_ends(withdraw_ends);
 }
 
 /// Retrieves the total staking amount
-pub fn staking_total() -> U256 {
-    get_key(STAKING_TOTAL).unwrap_or_default()
+pub fn staking_total<S: ContractStorage>(storage: &S) -> U256 {
+    Epoch::current(storage).staking_total()
 }
 
 /// Sets the total staking amount
-pub fn set_staking_total(staking_total: U256) {
-    set_key(STAKING_TOTAL, staking_total);
+pub fn set_staking_total<S: ContractStorage>(storage: &S, staking_total: U256) {
+    Epoch::current(storage).set_staking_total(staking_total);
 }
 
 /// Retrieves the total reward amount
-pub fn total_reward() -> U256 {
-    get_key(TOTAL_REWARD).unwrap_or_default()
+pub fn total_reward<S: ContractStorage>(storage: &S) -> U256 {
+    Epoch::current(storage).total_reward()
 }
 
 /// Sets the total reward amount
-pub fn set_total_reward(total_reward: U256) {
-    set_key(TOTAL_REWARD, total_reward);
+pub fn set_total_reward<S: ContractStorage>(storage: &S, total_reward: U256) {
+    Epoch::current(storage).set_total_reward(total_reward);
 }
 
 /// Retrieves the early withdrawal reward amount
-pub fn early_withdraw_reward() -> U256 {
-    get_key(EARLY_WITHDRAW_REWARD).unwrap_or_default()
+pub fn early_withdraw_reward<S: ContractStorage>(storage: &S) -> U256 {
+    Epoch::current(storage).early_withdraw_reward()
 }
 
 /// Sets the early withdrawal reward amount
-pub fn set_early_withdraw_reward(early_withdraw_reward: U256) {
-    set_key(EARLY_WITHDRAW_REWARD, early_withdraw_reward);
+pub fn set_early_withdraw_reward<S: ContractStorage>(storage: &S, early_withdraw_reward: U256) {
+    Epoch::current(storage).set_early_withdraw_reward(early_withdraw_reward);
 }
 
 /// Retrieves the total staked amount
-pub fn staked_total() -> U256 {
-    get_key(STAKED_TOTAL).unwrap_or_default()
+pub fn staked_total<S: ContractStorage>(storage: &S) -> U256 {
+    Epoch::current(storage).staked_total()
 }
 
 /// Sets staked total
-pub fn set_staked_total(staked_total: U256) {
-    set_key(STAKED_TOTAL, staked_total);
+pub fn set_staked_total<S: ContractStorage>(storage: &S, staked_total: U256) {
+    Epoch::current(storage).set_staked_total(staked_total);
 }
 
 /// Retrieves the reward balance
-pub fn reward_balance() -> U256 {
-    get_key(REWARD_BALANCE).unwrap_or_default()
+pub fn reward_balance<S: ContractStorage>(storage: &S) -> U256 {
+    Epoch::current(storage).reward_balance()
 }
 
 /// Sets the reward balance
-pub fn set_reward_balance(reward_balance: U256) {
-    set_key(REWARD_BALANCE, reward_balance);
+pub fn set_reward_balance<S: ContractStorage>(storage: &S, reward_balance: U256) {
+    Epoch::current(storage).set_reward_balance(reward_balance);
 }
 
 /// Retrieves the staked balance
-pub fn staked_balance() -> U256 {
-    get_key(STAKED_BALANCE).unwrap_or_default()
+pub fn staked_balance<S: ContractStorage>(storage: &S) -> U256 {
+    Epoch::current(storage).staked_balance()
 }
 
 /// Sets the staked balance
-pub fn set_staked_balance(staked_balance: U256) {
-    set_key(STAKED_BALANCE, staked_balance);
+pub fn set_staked_balance<S: ContractStorage>(storage: &S, staked_balance: U256) {
+    Epoch::current(storage).set_staked_balance(staked_balance);
+}
+
+/// Retrieves the total weight of the stakes
+pub fn weighted_total<S: ContractStorage>(storage: &S) -> U256 {
+    Epoch::current(storage).weighted_total()
+}
+
+/// Sets the total weight of the stakes
+pub fn set_weighted_total<S: ContractStorage>(storage: &S, weighted_total: U256) {
+    Epoch::current(storage).set_weighted_total(weighted_total);
+}
+
+/// Retrieves the weight of the stakes that haven't been withdrawn
+pub fn weighted_balance<S: ContractStorage>(storage: &S) -> U256 {
+    Epoch::current(storage).weighted_balance()
+}
+
+/// Sets the weight of the stakes that haven't been withdrawn
+pub fn set_weighted_balance<S: ContractStorage>(storage: &S, weighted_balance: U256) {
+    Epoch::current(storage).set_weighted_balance(weighted_balance);
+}
+
+/// Retrieves whether the contract is paused
+pub fn paused<S: ContractStorage>(storage: &S) -> bool {
+    get_key(storage, PAUSED).unwrap_or_default()
+}
+
+/// Sets whether the contract is paused
+pub fn set_paused<S: ContractStorage>(storage: &S, paused: bool) {
+    set_key(storage, PAUSED, paused);
+}
+
+/// Retrieves the penalty, in basis points, taken out of the principal of early withdrawals
+pub fn early_withdraw_penalty<S: ContractStorage>(storage: &S) -> u32 {
+    get_key(storage, EARLY_WITHDRAW_PENALTY).unwrap_or_default()
+}
+
+/// Sets the penalty, in basis points, taken out of the principal of early withdrawals
+pub fn set_early_withdraw_penalty<S: ContractStorage>(storage: &S, early_withdraw_penalty: u32) {
+    set_key(storage, EARLY_WITHDRAW_PENALTY, early_withdraw_penalty);
+}
+
+/// Retrieves the address early withdraw penalties are sent to, if any
+pub fn penalty_treasury<S: ContractStorage>(storage: &S) -> Option<Address> {
+    get_key(storage, PENALTY_TREASURY).unwrap_or_default()
+}
+
+/// Sets the address early withdraw penalties are sent to
+pub fn set_penalty_treasury<S: ContractStorage>(storage: &S, penalty_treasury: Option<Address>) {
+    set_key(storage, PENALTY_TREASURY, penalty_treasury);
+}
+
+/// Retrieves the symbol of the receipts
+pub fn symbol<S: ContractStorage>(storage: &S) -> String {
+    get_key(storage, SYMBOL).unwrap_or_revert_in(storage)
+}
+
+/// Sets the symbol of the receipts
+pub fn set_symbol<S: ContractStorage>(storage: &S, symbol: String) {
+    set_key(storage, SYMBOL, symbol);
+}
+
+/// Retrieves the decimals of the receipts
+pub fn decimals<S: ContractStorage>(storage: &S) -> u8 {
+    get_key(storage, DECIMALS).unwrap_or_revert_in(storage)
+}
+
+/// Sets the decimals of the receipts
+pub fn set_decimals<S: ContractStorage>(storage: &S, decimals: u8) {
+    set_key(storage, DECIMALS, decimals);
+}
+
+/// Retrieves the number of receipts in circulation
+pub fn total_supply<S: ContractStorage>(storage: &S) -> U256 {
+    get_key(storage, TOTAL_SUPPLY).unwrap_or_default()
+}
+
+/// Sets the number of receipts in circulation
+pub fn set_total_supply<S: ContractStorage>(storage: &S, total_supply: U256) {
+    set_key(storage, TOTAL_SUPPLY, total_supply);
+}
+
+/// Retrieves the version of the layout of the stored state
+pub fn schema_version<S: ContractStorage>(storage: &S) -> u32 {
+    get_key(storage, SCHEMA_VERSION).unwrap_or_default()
+}
+
+/// Sets the version of the layout of the stored state
+pub fn set_schema_version<S: ContractStorage>(storage: &S, schema_version: u32) {
+    set_key(storage, SCHEMA_VERSION, schema_version);
+}
+
+/// Retrieves the smallest amount a single stake can add
+pub fn min_stake<S: ContractStorage>(storage: &S) -> U256 {
+    get_key(storage, MIN_STAKE).unwrap_or_default()
+}
+
+/// Sets the smallest amount a single stake can add
+pub fn set_min_stake<S: ContractStorage>(storage: &S, min_stake: U256) {
+    set_key(storage, MIN_STAKE, min_stake);
+}
+
+/// Retrieves the largest amount an address can have staked
+pub fn max_stake_per_address<S: ContractStorage>(storage: &S) -> U256 {
+    get_key(storage, MAX_STAKE_PER_ADDRESS).unwrap_or_default()
+}
+
+/// Sets the largest amount an address can have staked
+pub fn set_max_stake_per_address<S: ContractStorage>(storage: &S, max_stake_per_address: U256) {
+    set_key(storage, MAX_STAKE_PER_ADDRESS, max_stake_per_address);
+}
+
+/// Retrieves whether only allowlisted addresses can stake
+pub fn allowlist_enabled<S: ContractStorage>(storage: &S) -> bool {
+    get_key(storage, ALLOWLIST_ENABLED).unwrap_or_default()
+}
+
+/// Sets whether only allowlisted addresses can stake
+pub fn set_allowlist_enabled<S: ContractStorage>(storage: &S, allowlist_enabled: bool) {
+    set_key(storage, ALLOWLIST_ENABLED, allowlist_enabled);
+}
+
+/// Retrieves the root of the Merkle tree of allowlisted addresses
+pub fn merkle_root<S: ContractStorage>(storage: &S) -> Option<[u8; 32]> {
+    get_key(storage, MERKLE_ROOT).unwrap_or_default()
+}
+
+/// Sets the root of the Merkle tree of allowlisted addresses
+pub fn set_merkle_root<S: ContractStorage>(storage: &S, merkle_root: Option<[u8; 32]>) {
+    set_key(storage, MERKLE_ROOT, merkle_root);
+}
+
+/// Retrieves the pool mode
+pub fn pool_mode<S: ContractStorage>(storage: &S) -> u8 {
+    get_key(storage, POOL_MODE).unwrap_or_default()
+}
+
+/// Sets the pool mode
+pub fn set_pool_mode<S: ContractStorage>(storage: &S, pool_mode: u8) {
+    set_key(storage, POOL_MODE, pool_mode);
+}
+
+/// Retrieves the reward streamed per second in streaming mode
+pub fn reward_rate<S: ContractStorage>(storage: &S) -> U256 {
+    get_key(storage, REWARD_RATE).unwrap_or_default()
+}
+
+/// Sets the reward streamed per second in streaming mode
+pub fn set_reward_rate<S: ContractStorage>(storage: &S, reward_rate: U256) {
+    set_key(storage, REWARD_RATE, reward_rate);
+}
+
+/// Retrieves the accumulated reward per staked token in streaming mode
+pub fn acc_reward_per_share<S: ContractStorage>(storage: &S) -> U256 {
+    get_key(storage, ACC_REWARD_PER_SHARE).unwrap_or_default()
+}
+
+/// Sets the accumulated reward per staked token in streaming mode
+pub fn set_acc_reward_per_share<S: ContractStorage>(storage: &S, acc_reward_per_share: U256) {
+    set_key(storage, ACC_REWARD_PER_SHARE, acc_reward_per_share);
+}
+
+/// Retrieves the time up to which the accumulated reward per staked token is computed
+pub fn last_reward_time<S: ContractStorage>(storage: &S) -> u64 {
+    get_key(storage, LAST_REWARD_TIME).unwrap_or_default()
+}
+
+/// Sets the time up to which the accumulated reward per staked token is computed
+pub fn set_last_reward_time<S: ContractStorage>(storage: &S, last_reward_time: u64) {
+    set_key(storage, LAST_REWARD_TIME, last_reward_time);
 }
 
 /// Retrieves the contract package hash
-pub fn contract_package_hash() -> ContractPackageHash {
-    let call_stacks = get_call_stack();
-    let last_entry = call_stacks.last().unwrap_or_revert();
+pub fn contract_package_hash<S: ContractStorage>(storage: &S) -> ContractPackageHash {
+    let call_stacks = storage.call_stack();
+    let last_entry = call_stacks.last().unwrap_or_revert_in(storage);
     let package_hash: Option<ContractPackageHash> = match last_entry {
         CallStackElement::StoredContract {
             contract_package_hash,
@@ -226,79 +778,129 @@ pub fn contract_package_hash() -> ContractPackageHash {
         } => Some(*contract_package_hash),
         _ => None,
     };
-    package_hash.unwrap_or_revert()
+    package_hash.unwrap_or_revert_in(storage)
 }
 
-/// Emits a StakingContractEvent
-pub fn emit(event: &StakingContractEvent) {
-    let mut events = Vec::new();
-    let package = contract_package_hash();
-    match event {
+/// Emits a StakingContractEvent under the Casper Event Standard
+pub fn emit<S: ContractStorage>(storage: &S, event: &StakingContractEvent) {
+    match event.clone() {
         StakingContractEvent::Stake {
             token_address,
             staker_address,
+            payer_address,
             requested_amount,
             staked_amount,
-        } => {
-            let mut param = BTreeMap::new();
-            param.insert(CONTRACT_PACKAGE_HASH, package.to_string());
-            param.insert(EVENT_TYPE, STAKE.to_string());
-            param.insert(TOKEN_ADDRESS, token_address.to_string());
-            param.insert(
-                STAKER_ADDRESS,
-                TryInto::<String>::try_into(*staker_address).unwrap(),
-            );
-            param.insert(REQUESTED_AMOUNT, requested_amount.to_string());
-            param.insert(STAKED_AMOUNT, staked_amount.to_string());
-            events.push(param);
-        }
+        } => ces::emit(
+            storage,
+            &event::Stake {
+                token_address,
+                staker_address,
+                payer_address,
+                requested_amount,
+                staked_amount,
+            },
+        ),
         StakingContractEvent::PaidOut {
             token_address,
             staker_address,
             amount,
             reward,
-        } => {
-            let mut param = BTreeMap::new();
-            param.insert(CONTRACT_PACKAGE_HASH, package.to_string());
-            param.insert(EVENT_TYPE, PAID_OUT.to_string());
-            param.insert(TOKEN_ADDRESS, token_address.to_string());
-            param.insert(
-                STAKER_ADDRESS,
-                TryInto::<String>::try_into(*staker_address).unwrap(),
-            );
-            param.insert(AMOUNT, amount.to_string());
-            param.insert(REWARD, reward.to_string());
-            events.push(param);
-        }
+            penalty,
+        } => ces::emit(
+            storage,
+            &event::PaidOut {
+                token_address,
+                staker_address,
+                amount,
+                reward,
+                penalty,
+            },
+        ),
         StakingContractEvent::AddReward {
+            token_address,
+            account,
             reward_amount,
             withdrawable_amount,
-        } => {
-            let mut param = BTreeMap::new();
-            param.insert(CONTRACT_PACKAGE_HASH, package.to_string());
-            param.insert(EVENT_TYPE, ADD_REWARD.to_string());
-            param.insert(REWARD_AMOUNT, reward_amount.to_string());
-            param.insert(WITHDRAWABLE_AMOUNT, withdrawable_amount.to_string());
-            events.push(param);
+        } => ces::emit(
+            storage,
+            &event::AddReward {
+                token_address,
+                account,
+                reward_amount,
+                withdrawable_amount,
+            },
+        ),
+        StakingContractEvent::Compounded {
+            token_address,
+            staker_address,
+            reward,
+            staked_amount,
+        } => ces::emit(
+            storage,
+            &event::Compounded {
+                token_address,
+                staker_address,
+                reward,
+                staked_amount,
+            },
+        ),
+        StakingContractEvent::Paused { account } => ces::emit(storage, &event::Paused { account }),
+        StakingContractEvent::Unpaused { account } => {
+            ces::emit(storage, &event::Unpaused { account })
         }
+        StakingContractEvent::StakeTransferred {
+            token_address,
+            staker_address,
+            recipient_address,
+            amount,
+        } => ces::emit(
+            storage,
+            &event::StakeTransferred {
+                token_address,
+                staker_address,
+                recipient_address,
+                amount,
+            },
+        ),
+        StakingContractEvent::EmergencyWithdraw {
+            token_address,
+            staker_address,
+            amount,
+        } => ces::emit(
+            storage,
+            &event::EmergencyWithdraw {
+                token_address,
+                staker_address,
+                amount,
+            },
+        ),
         StakingContractEvent::Refunded {
             token_address,
             staker_address,
             amount,
-        } => {
-            let mut param = BTreeMap::new();
-            param.insert(CONTRACT_PACKAGE_HASH, package.to_string());
-            param.insert(EVENT_TYPE, REFUNDED.to_string());
-            param.insert(TOKEN_ADDRESS, token_address.to_string());
-            param.insert(
-                STAKER_ADDRESS,
-                TryInto::<String>::try_into(*staker_address).unwrap(),
-            );
-            param.insert(AMOUNT, amount.to_string());
-            events.push(param);
-        }
+        } => ces::emit(
+            storage,
+            &event::Refunded {
+                token_address,
+                staker_address,
+                amount,
+            },
+        ),
+        StakingContractEvent::RolledOver {
+            token_address,
+            staker_address,
+            epoch,
+            amount,
+            reward,
+        } => ces::emit(
+            storage,
+            &event::RolledOver {
+                token_address,
+                staker_address,
+                epoch,
+                amount,
+                reward,
+            },
+        ),
     };
-    for param in events {
-        let _: URef = storage::new_uref(param);
-    }
 }
@@ -1,45 +1,72 @@
 //! Implementation details.
 use core::convert::TryInto;
 
-use casper_contract::{
-    contract_api::{runtime, storage},
-    unwrap_or_revert::UnwrapOrRevert,
+use casper_contract::{contract_api::runtime, ext_ffi};
+use casper_types::{
+    api_error, bytesrepr::FromBytes, runtime_args, system::CallStackElement, ApiError, CLTyped,
+    ContractPackageHash, RuntimeArgs, URef, U256,
 };
-use casper_types::{bytesrepr::FromBytes, system::CallStackElement, ApiError, CLTyped, URef};
+
+use contract_utils::{ContractStorage, UnwrapOrRevertIn};
 
 use crate::address::Address;
 use crate::error::Error;
 
+const ENTRY_POINT_TRANSFER: &str = "transfer";
+const ENTRY_POINT_TRANSFER_FROM: &str = "transfer_from";
+const ENTRY_POINT_DECIMALS: &str = "decimals";
+
 /// Gets [`URef`] under a name.
 #[allow(unused)]
-pub(crate) fn get_uref(name: &str) -> URef {
-    let key = runtime::get_key(name)
+pub(crate) fn get_uref<S: ContractStorage>(storage: &S, name: &str) -> URef {
+    let key = storage
+        .get_named_key(name)
         .ok_or(ApiError::MissingKey)
-        .unwrap_or_revert();
-    key.try_into().unwrap_or_revert()
+        .unwrap_or_revert_in(storage);
+    key.try_into().unwrap_or_revert_in(storage)
 }
 
 /// Reads value from a named key.
 #[allow(unused)]
-pub(crate) fn read_from<T>(name: &str) -> T
+pub(crate) fn read_from<S, T>(storage: &S, name: &str) -> T
 where
+    S: ContractStorage,
     T: FromBytes + CLTyped,
 {
-    let uref = get_uref(name);
-    let value: T = storage::read(uref).unwrap_or_revert().unwrap_or_revert();
+    let uref = get_uref(storage, name);
+    let value: T = storage.read(uref).unwrap_or_revert_in(storage);
     value
 }
 
+/// Reads a named argument that the caller is allowed to omit.
+///
+/// Returns `None` when the argument was not passed, and reverts when it was passed with a
+/// different type.
+pub fn get_optional_named_arg<T: FromBytes>(name: &str) -> Option<T> {
+    let mut arg_size: usize = 0;
+    let ret = unsafe {
+        ext_ffi::casper_get_named_arg_size(
+            name.as_bytes().as_ptr(),
+            name.len(),
+            &mut arg_size as *mut usize,
+        )
+    };
+    match api_error::result_from(ret) {
+        Ok(()) => Some(runtime::get_named_arg(name)),
+        Err(ApiError::MissingArgument) => None,
+        Err(error) => runtime::revert(error),
+    }
+}
+
 /// Gets the immediate call stack element of the current execution.
-fn get_immediate_call_stack_item() -> Option<CallStackElement> {
-    let call_stack = runtime::get_call_stack();
-    call_stack.into_iter().rev().nth(1)
+fn get_immediate_call_stack_item<S: ContractStorage>(storage: &S) -> Option<CallStackElement> {
+    storage.call_stack().iter().rev().nth(1).cloned()
 }
 
 /// Returns address based on a [`CallStackElement`].
 ///
 /// For `Session` and `StoredSession` variants it will return account hash, and for `StoredContract`
-/// case it will use contract hash as the address.
+/// case it will use contract package hash as the address.
 fn call_stack_element_to_address(call_stack_element: CallStackElement) -> Address {
     match call_stack_element {
         CallStackElement::Session { account_hash } => Address::from(account_hash),
@@ -55,12 +82,14 @@ fn call_stack_element_to_address(call_stack_element: CallStackElement) -> Addres
     }
 }
 
-/// Gets the immediate session caller of the current execution.
+/// Gets the immediate caller of the current execution.
 ///
-/// This function ensures that only session code can execute this function, and disallows stored
-/// session/stored contracts.
-pub(crate) fn get_immediate_caller_address() -> Result<Address, Error> {
-    get_immediate_call_stack_item()
+/// Accounts are identified by their account hash, and stored contracts by their contract package
+/// hash, so a contract keeps the same address across upgrades.
+pub(crate) fn get_immediate_caller_address<S: ContractStorage>(
+    storage: &S,
+) -> Result<Address, Error> {
+    get_immediate_call_stack_item(storage)
         .map(call_stack_element_to_address)
         .ok_or(Error::InvalidContext)
 }
@@ -69,13 +98,58 @@ pub(crate) fn get_immediate_caller_address() -> Result<Address, Error> {
 ///
 /// This is similar to what [`runtime::get_caller`] does but it also supports stored contracts.
 #[allow(unused)]
-pub(crate) fn get_caller_address() -> Result<Address, Error> {
-    let call_stack = runtime::get_call_stack();
-    let top_of_the_stack = call_stack
-        .into_iter()
-        .rev()
-        .next()
-        .ok_or(Error::InvalidContext)?;
-    let address = call_stack_element_to_address(top_of_the_stack);
+pub(crate) fn get_caller_address<S: ContractStorage>(storage: &S) -> Result<Address, Error> {
+    let top_of_the_stack = storage.call_stack().last().ok_or(Error::InvalidContext)?;
+    let address = call_stack_element_to_address(top_of_the_stack.clone());
     Ok(address)
 }
+
+/// Reads a [`ContractPackageHash`] stored as a `Key::Hash` under a named key.
+pub(crate) fn get_contract_package_hash<S: ContractStorage>(
+    storage: &S,
+    name: &str,
+) -> ContractPackageHash {
+    #[allow(clippy::redundant_closure)]
+    storage
+        .get_named_key(name)
+        .unwrap_or_revert_with_in(storage, Error::MissingContractPackageHash)
+        .into_hash()
+        .map(|hash_address| ContractPackageHash::new(hash_address))
+        .unwrap_or_revert_with_in(storage, Error::InvalidContractPackageHash)
+}
+
+/// Returns the decimals of an ERC-20 token.
+pub(crate) fn decimals<S: ContractStorage>(storage: &S, token: ContractPackageHash) -> u8 {
+    storage.call_versioned_contract(token, ENTRY_POINT_DECIMALS, runtime_args! {})
+}
+
+/// Transfers `amount` of an ERC-20 token held by the current contract to `recipient`.
+pub(crate) fn transfer<S: ContractStorage>(
+    storage: &S,
+    token: ContractPackageHash,
+    recipient: Address,
+    amount: U256,
+) {
+    let args = runtime_args! {
+        "recipient" => recipient,
+        "amount" => amount,
+    };
+    storage.call_versioned_contract::<()>(token, ENTRY_POINT_TRANSFER, args);
+}
+
+/// Transfers `amount` of an ERC-20 token from `owner` to `recipient`, using the allowance the
+/// owner gave to the current contract.
+pub(crate) fn transfer_from<S: ContractStorage>(
+    storage: &S,
+    token: ContractPackageHash,
+    owner: Address,
+    recipient: Address,
+    amount: U256,
+) {
+    let args = runtime_args! {
+        "owner" => owner,
+        "recipient" => recipient,
+        "amount" => amount
+    };
+    storage.call_versioned_contract::<()>(token, ENTRY_POINT_TRANSFER_FROM, args);
+}
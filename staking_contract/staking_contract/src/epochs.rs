@@ -0,0 +1,236 @@
+//! Storage of the epochs of the staking contract.
+//!
+//! Every epoch has its own windows, cap and reward accounting. Its fields live in the `epochs`
+//! dictionary under a key namespaced by the epoch id, e.g. `3_staked_total`, and the epoch the
+//! stake of every address belongs to lives in the `staker_epochs` dictionary.
+use crate::data::{
+    EARLY_WITHDRAW_REWARD, REWARD_BALANCE, STAKED_BALANCE, STAKED_TOTAL, STAKING_ENDS,
+    STAKING_STARTS, STAKING_TOTAL, TOTAL_REWARD, WEIGHTED_BALANCE, WEIGHTED_TOTAL, WITHDRAW_ENDS,
+    WITHDRAW_STARTS,
+};
+use crate::error::Error;
+use alloc::format;
+use casper_types::{
+    bytesrepr::{FromBytes, ToBytes},
+    CLTyped, Key, U256,
+};
+use contract_utils::{get_key, set_key, ContractStorage, Dict, UnwrapOrRevertIn};
+use staking_math::pool::FixedWindowPool;
+
+// Dictionary key for storing the fields of every epoch
+const EPOCHS_DICT: &str = "epochs";
+// Dictionary key for storing the epoch the stake of addresses belongs to
+const STAKER_EPOCHS_DICT: &str = "staker_epochs";
+// Named key for storing the id of the current epoch
+pub const CURRENT_EPOCH: &str = "current_epoch";
+
+// Structure for managing a single epoch
+pub struct Epoch<'a, S: ContractStorage> {
+    storage: &'a S,
+    id: u64,
+    fields: Dict<'a, S>,
+}
+
+impl<'a, S: ContractStorage> Epoch<'a, S> {
+    /// Initializes the epoch dictionaries, starting at epoch 0
+    pub fn init(storage: &S) {
+        Dict::init(storage, EPOCHS_DICT);
+        Dict::init(storage, STAKER_EPOCHS_DICT);
+        set_key(storage, CURRENT_EPOCH, 0u64);
+    }
+
+    /// Returns the id of the current epoch
+    pub fn current_id(storage: &S) -> u64 {
+        get_key(storage, CURRENT_EPOCH).unwrap_or_default()
+    }
+
+    /// Returns the current epoch
+    pub fn current(storage: &'a S) -> Epoch<'a, S> {
+        Self::at(storage, Self::current_id(storage))
+    }
+
+    /// Moves on to the next epoch and returns it
+    pub fn schedule(storage: &'a S) -> Epoch<'a, S> {
+        let id = Self::current_id(storage) + 1;
+        set_key(storage, CURRENT_EPOCH, id);
+        Self::at(storage, id)
+    }
+
+    /// Returns the epoch with the given id
+    pub fn instance(storage: &'a S, id: u64) -> Result<Epoch<'a, S>, Error> {
+        if id > Self::current_id(storage) {
+            return Err(Error::UnknownEpoch);
+        }
+        Ok(Self::at(storage, id))
+    }
+
+    fn at(storage: &'a S, id: u64) -> Epoch<'a, S> {
+        Epoch {
+            storage,
+            id,
+            fields: Dict::instance(storage, EPOCHS_DICT),
+        }
+    }
+
+    /// Returns the epoch id
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns whether this is the current epoch
+    pub fn is_current(&self) -> bool {
+        self.id == Self::current_id(self.storage)
+    }
+
+    fn get<T: CLTyped + FromBytes>(&self, field: &str) -> Option<T> {
+        self.fields.get(&format!("{}_{}", self.id, field))
+    }
+
+    fn set<T: CLTyped + ToBytes>(&self, field: &str, value: T) {
+        self.fields.set(&format!("{}_{}", self.id, field), value);
+    }
+
+    /// Returns the state of the epoch the reward math works on
+    pub fn state(&self) -> FixedWindowPool {
+        FixedWindowPool {
+            staking_ends: self.staking_ends(),
+            withdraw_ends: self.withdraw_ends(),
+            early_withdraw_reward: self.early_withdraw_reward(),
+            reward_balance: self.reward_balance(),
+            weighted_total: self.weighted_total(),
+            weighted_balance: self.weighted_balance(),
+        }
+    }
+
+    /// Retrieves the staking start time
+    pub fn staking_starts(&self) -> u64 {
+        self.get(STAKING_STARTS).unwrap_or_revert_in(self.storage)
+    }
+
+    /// Sets the staking start time
+    pub fn set_staking_starts(&self, staking_starts: u64) {
+        self.set(STAKING_STARTS, staking_starts);
+    }
+
+    /// Retrieves the staking end time
+    pub fn staking_ends(&self) -> u64 {
+        self.get(STAKING_ENDS).unwrap_or_revert_in(self.storage)
+    }
+
+    /// Sets the staking end time
+    pub fn set_staking_ends(&self, staking_ends: u64) {
+        self.set(STAKING_ENDS, staking_ends);
+    }
+
+    /// Retrieves the withdrawal start time
+    pub fn withdraw_starts(&self) -> u64 {
+        self.get(WITHDRAW_STARTS).unwrap_or_default()
+    }
+
+    /// Sets the withdrawal start time
+    pub fn set_withdraw_starts(&self, withdraw_starts: u64) {
+        self.set(WITHDRAW_STARTS, withdraw_starts);
+    }
+
+    /// Retrieves the withdrawal end time
+    pub fn withdraw_ends(&self) -> u64 {
+        self.get(WITHDRAW_ENDS).unwrap_or_default()
+    }
+
+    /// Sets the withdrawal end time
+    pub fn set_withdraw_ends(&self, withdraw_ends: u64) {
+        self.set(WITHDRAW_ENDS, withdraw_ends);
+    }
+
+    /// Retrieves the total staking amount
+    pub fn staking_total(&self) -> U256 {
+        self.get(STAKING_TOTAL).unwrap_or_default()
+    }
+
+    /// Sets the total staking amount
+    pub fn set_staking_total(&self, staking_total: U256) {
+        self.set(STAKING_TOTAL, staking_total);
+    }
+
+    /// Retrieves the total reward amount
+    pub fn total_reward(&self) -> U256 {
+        self.get(TOTAL_REWARD).unwrap_or_default()
+    }
+
+    /// Sets the total reward amount
+    pub fn set_total_reward(&self, total_reward: U256) {
+        self.set(TOTAL_REWARD, total_reward);
+    }
+
+    /// Retrieves the early withdrawal reward amount
+    pub fn early_withdraw_reward(&self) -> U256 {
+        self.get(EARLY_WITHDRAW_REWARD).unwrap_or_default()
+    }
+
+    /// Sets the early withdrawal reward amount
+    pub fn set_early_withdraw_reward(&self, early_withdraw_reward: U256) {
+        self.set(EARLY_WITHDRAW_REWARD, early_withdraw_reward);
+    }
+
+    /// Retrieves the total staked amount
+    pub fn staked_total(&self) -> U256 {
+        self.get(STAKED_TOTAL).unwrap_or_default()
+    }
+
+    /// Sets staked total
+    pub fn set_staked_total(&self, staked_total: U256) {
+        self.set(STAKED_TOTAL, staked_total);
+    }
+
+    /// Retrieves the reward balance
+    pub fn reward_balance(&self) -> U256 {
+        self.get(REWARD_BALANCE).unwrap_or_default()
+    }
+
+    /// Sets the reward balance
+    pub fn set_reward_balance(&self, reward_balance: U256) {
+        self.set(REWARD_BALANCE, reward_balance);
+    }
+
+    /// Retrieves the staked balance
+    pub fn staked_balance(&self) -> U256 {
+        self.get(STAKED_BALANCE).unwrap_or_default()
+    }
+
+    /// Sets the staked balance
+    pub fn set_staked_balance(&self, staked_balance: U256) {
+        self.set(STAKED_BALANCE, staked_balance);
+    }
+
+    /// Retrieves the total weight of the stakes
+    pub fn weighted_total(&self) -> U256 {
+        self.get(WEIGHTED_TOTAL).unwrap_or_default()
+    }
+
+    /// Sets the total weight of the stakes
+    pub fn set_weighted_total(&self, weighted_total: U256) {
+        self.set(WEIGHTED_TOTAL, weighted_total);
+    }
+
+    /// Retrieves the weight of the stakes that haven't been withdrawn
+    pub fn weighted_balance(&self) -> U256 {
+        self.get(WEIGHTED_BALANCE).unwrap_or_default()
+    }
+
+    /// Sets the weight of the stakes that haven't been withdrawn
+    pub fn set_weighted_balance(&self, weighted_balance: U256) {
+        self.set(WEIGHTED_BALANCE, weighted_balance);
+    }
+}
+
+/// Retrieves the epoch the stake of the given address belongs to
+pub fn staker_epoch<S: ContractStorage>(storage: &S, address: &Key) -> u64 {
+    Dict::instance(storage, STAKER_EPOCHS_DICT)
+        .get_by_key(address)
+        .unwrap_or_default()
+}
+
+/// Sets the epoch the stake of the given address belongs to
+pub fn set_staker_epoch<S: ContractStorage>(storage: &S, address: &Key, epoch: u64) {
+    Dict::instance(storage, STAKER_EPOCHS_DICT).set_by_key(address, epoch);
+}
@@ -1,5 +1,6 @@
 // This code defines an enum for the errors that can occur in the staking contract.
 use casper_types::ApiError;
+use staking_math::error::MathError;
 
 /// An enum for the errors that can occur in the staking contract.
 #[derive(Debug)]
@@ -49,6 +50,73 @@ pub enum Error {
     CheckedSub = 23,
     /// Gap between staking_ends and withdraw_starts
     GapBetweenStakingEndsWithdrawStarts = 24,
+    /// Staking total is lower than the amount already staked
+    StakingTotalBelowStakedTotal = 25,
+    /// No pool with the given id
+    UnknownPool = 26,
+    /// Pool mode is neither fixed window nor streaming
+    UnknownPoolMode = 27,
+    /// The operation is only available in streaming mode
+    NotStreamingPool = 28,
+    /// The staker has no unclaimed reward
+    NothingToClaim = 29,
+    /// Rewards are paid in another token than the staked one
+    RewardTokenNotStakedToken = 30,
+    /// The contract is paused
+    ContractPaused = 31,
+    /// The contract is not paused
+    ContractNotPaused = 32,
+    /// No lock tier is configured for the lock duration
+    UnknownLockTier = 33,
+    /// The stake is still locked
+    StakeLocked = 34,
+    /// The staker has no position with the given id
+    UnknownPosition = 35,
+    /// The early withdraw penalty must be below 10000 basis points
+    InvalidPenalty = 36,
+    /// The spender is not allowed to move that many receipts
+    InsufficientAllowance = 37,
+    /// The staker is not on the allowlist of the campaign
+    NotAllowlisted = 38,
+    /// The stake is below the minimum stake
+    BelowMinStake = 39,
+    /// The stake would take the address above the maximum stake per address
+    AboveMaxStake = 40,
+    /// The minimum stake is above the maximum stake per address
+    InvalidStakeLimits = 41,
+    /// The stored state is newer than the contract code
+    UnknownSchemaVersion = 42,
+    /// No epoch with the given id
+    UnknownEpoch = 43,
+    /// The withdraw window of the current epoch is still open
+    EpochNotClosed = 44,
+    /// The stake belongs to a past epoch, and must be rolled over or withdrawn first
+    StakeInPastEpoch = 45,
+    /// The stake already belongs to the current epoch
+    StakeInCurrentEpoch = 46,
+    /// The operation is only available in fixed window mode
+    NotFixedWindowPool = 47,
+    /// An amount does not fit in 256 bits
+    Overflow = 48,
+    /// A reward formula divides by zero
+    DivisionByZero = 49,
+    /// The new end of a window is already in the past
+    WindowEndsInPast = 50,
+    /// The caller is not an admin of the contract
+    NotAnAdmin = 51,
+    /// Only the staker can lock their own stake
+    LockForAnotherStaker = 52,
+}
+
+impl From<MathError> for Error {
+    /// Converts a `MathError` of the reward math to an `Error`.
+    fn from(error: MathError) -> Error {
+        match error {
+            MathError::Overflow => Error::Overflow,
+            MathError::CheckedSub => Error::CheckedSub,
+            MathError::DivisionByZero => Error::DivisionByZero,
+        }
+    }
 }
 
 impl From<Error> for ApiError {
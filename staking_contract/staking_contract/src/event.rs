@@ -1,9 +1,17 @@
-// This code defines an enum for the events that can be emitted by the staking contract.
-use crate::address::Address;
-use alloc::string::String;
-use casper_types::U256;
+// This code defines an enum for the events that can be emitted by the staking contract, and the
+// typed events of the Casper Event Standard they are written as.
+use crate::{
+    address::Address,
+    ces::{EventInstance, Schema, Schemas},
+};
+use alloc::{string::String, vec::Vec};
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    CLType, CLTyped, U256,
+};
 
 /// An enum for the events that can be emitted by the staking contract.
+#[derive(Clone)]
 pub enum StakingContractEvent {
     /// Event emitted when a user stakes tokens.
     Stake {
@@ -11,6 +19,8 @@ pub enum StakingContractEvent {
         token_address: String,
         /// The address of the staker.
         staker_address: Address,
+        /// The address the staked tokens were taken from.
+        payer_address: Address,
         /// The amount of tokens requested to be staked.
         requested_amount: U256,
         /// The amount of tokens that were actually staked.
@@ -26,14 +36,61 @@ pub enum StakingContractEvent {
         amount: U256,
         /// The amount of rewards paid out.
         reward: U256,
+        /// The amount of tokens taken out of the withdrawal as an early withdraw penalty.
+        penalty: U256,
     },
     /// Event emitted when rewards are added to the staking contract.
     AddReward {
+        /// The address of the token contract.
+        token_address: String,
+        /// The address of the account that added the rewards.
+        account: Address,
         /// The amount of rewards added.
         reward_amount: U256,
         /// The amount of rewards that are now withdrawable.
         withdrawable_amount: U256,
     },
+    /// Event emitted when a user adds their reward to their stake.
+    Compounded {
+        /// The address of the token contract.
+        token_address: String,
+        /// The address of the staker.
+        staker_address: Address,
+        /// The amount of rewards added to the stake.
+        reward: U256,
+        /// The amount of tokens staked once the rewards are added.
+        staked_amount: U256,
+    },
+    /// Event emitted when an admin pauses the staking contract.
+    Paused {
+        /// The address of the admin.
+        account: Address,
+    },
+    /// Event emitted when an admin unpauses the staking contract.
+    Unpaused {
+        /// The address of the admin.
+        account: Address,
+    },
+    /// Event emitted when a user moves part of their stake to another address.
+    StakeTransferred {
+        /// The address of the token contract.
+        token_address: String,
+        /// The address of the staker the stake comes from.
+        staker_address: Address,
+        /// The address the stake goes to.
+        recipient_address: Address,
+        /// The amount of staked tokens moved.
+        amount: U256,
+    },
+    /// Event emitted when a user withdraws their principal and gives up their rewards.
+    EmergencyWithdraw {
+        /// The address of the token contract.
+        token_address: String,
+        /// The address of the staker.
+        staker_address: Address,
+        /// The amount of tokens withdrawn.
+        amount: U256,
+    },
     /// Event emitted when a user is refunded tokens.
     Refunded {
         /// The address of the token contract.
@@ -43,4 +100,214 @@ pub enum StakingContractEvent {
         /// The amount of tokens refunded.
         amount: U256,
     },
+    /// Event emitted when a user rolls their stake over into the current epoch.
+    RolledOver {
+        /// The address of the token contract.
+        token_address: String,
+        /// The address of the staker.
+        staker_address: Address,
+        /// The epoch the stake now belongs to.
+        epoch: u64,
+        /// The amount of tokens rolled over.
+        amount: U256,
+        /// The reward of the past epoch paid out.
+        reward: U256,
+    },
+}
+
+// Declares a typed event of the Casper Event Standard: a struct whose byte representation is its
+// prefixed name followed by its fields, in order.
+macro_rules! ces_event {
+    ($(#[$meta:meta])* $name:ident { $($(#[$field_meta:meta])* $field:ident: $ty:ty,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct $name {
+            $($(#[$field_meta])* pub $field: $ty,)*
+        }
+
+        impl EventInstance for $name {
+            fn name() -> String {
+                String::from(stringify!($name))
+            }
+
+            fn schema() -> Schema {
+                Schema::new()$(.with_elem(stringify!($field), <$ty>::cl_type()))*
+            }
+        }
+
+        impl CLTyped for $name {
+            fn cl_type() -> CLType {
+                CLType::Any
+            }
+        }
+
+        impl ToBytes for $name {
+            fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+                let mut result = bytesrepr::allocate_buffer(self)?;
+                result.append(&mut Self::prefixed_name().to_bytes()?);
+                $(result.append(&mut self.$field.to_bytes()?);)*
+                Ok(result)
+            }
+
+            fn serialized_length(&self) -> usize {
+                Self::prefixed_name().serialized_length() $(+ self.$field.serialized_length())*
+            }
+        }
+
+        impl FromBytes for $name {
+            fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+                let (prefixed_name, bytes) = String::from_bytes(bytes)?;
+                if prefixed_name != Self::prefixed_name() {
+                    return Err(bytesrepr::Error::Formatting);
+                }
+                $(let ($field, bytes) = <$ty>::from_bytes(bytes)?;)*
+                Ok((Self { $($field,)* }, bytes))
+            }
+        }
+    };
+}
+
+ces_event!(
+    /// A user staked tokens.
+    Stake {
+        /// The address of the token contract.
+        token_address: String,
+        /// The address of the staker.
+        staker_address: Address,
+        /// The address the staked tokens were taken from.
+        payer_address: Address,
+        /// The amount of tokens requested to be staked.
+        requested_amount: U256,
+        /// The amount of tokens that were actually staked.
+        staked_amount: U256,
+    }
+);
+
+ces_event!(
+    /// A user was paid out tokens and rewards.
+    PaidOut {
+        /// The address of the token contract.
+        token_address: String,
+        /// The address of the staker.
+        staker_address: Address,
+        /// The amount of tokens paid out.
+        amount: U256,
+        /// The amount of rewards paid out.
+        reward: U256,
+        /// The amount of tokens taken out of the withdrawal as an early withdraw penalty.
+        penalty: U256,
+    }
+);
+
+ces_event!(
+    /// Rewards were added to the staking contract.
+    AddReward {
+        /// The address of the token contract.
+        token_address: String,
+        /// The address of the account that added the rewards.
+        account: Address,
+        /// The amount of rewards added.
+        reward_amount: U256,
+        /// The amount of rewards that are now withdrawable.
+        withdrawable_amount: U256,
+    }
+);
+
+ces_event!(
+    /// A user added their reward to their stake.
+    Compounded {
+        /// The address of the token contract.
+        token_address: String,
+        /// The address of the staker.
+        staker_address: Address,
+        /// The amount of rewards added to the stake.
+        reward: U256,
+        /// The amount of tokens staked once the rewards are added.
+        staked_amount: U256,
+    }
+);
+
+ces_event!(
+    /// An admin paused the staking contract.
+    Paused {
+        /// The address of the admin.
+        account: Address,
+    }
+);
+
+ces_event!(
+    /// An admin unpaused the staking contract.
+    Unpaused {
+        /// The address of the admin.
+        account: Address,
+    }
+);
+
+ces_event!(
+    /// A user moved part of their stake to another address.
+    StakeTransferred {
+        /// The address of the token contract.
+        token_address: String,
+        /// The address of the staker the stake comes from.
+        staker_address: Address,
+        /// The address the stake goes to.
+        recipient_address: Address,
+        /// The amount of staked tokens moved.
+        amount: U256,
+    }
+);
+
+ces_event!(
+    /// A user withdrew their principal and gave up their rewards.
+    EmergencyWithdraw {
+        /// The address of the token contract.
+        token_address: String,
+        /// The address of the staker.
+        staker_address: Address,
+        /// The amount of tokens withdrawn.
+        amount: U256,
+    }
+);
+
+ces_event!(
+    /// A user was refunded tokens.
+    Refunded {
+        /// The address of the token contract.
+        token_address: String,
+        /// The address of the staker.
+        staker_address: Address,
+        /// The amount of tokens refunded.
+        amount: U256,
+    }
+);
+
+ces_event!(
+    /// A user rolled their stake over into the current epoch.
+    RolledOver {
+        /// The address of the token contract.
+        token_address: String,
+        /// The address of the staker.
+        staker_address: Address,
+        /// The epoch the stake now belongs to.
+        epoch: u64,
+        /// The amount of tokens rolled over.
+        amount: U256,
+        /// The reward of the past epoch paid out.
+        reward: U256,
+    }
+);
+
+/// Returns the schemas of all the events the staking contract and the staking factory emit.
+pub fn schemas() -> Schemas {
+    Schemas::new()
+        .with::<Stake>()
+        .with::<PaidOut>()
+        .with::<AddReward>()
+        .with::<Compounded>()
+        .with::<Paused>()
+        .with::<Unpaused>()
+        .with::<StakeTransferred>()
+        .with::<EmergencyWithdraw>()
+        .with::<Refunded>()
+        .with::<RolledOver>()
 }
@@ -0,0 +1,166 @@
+//! Views that bundle the state of the pool, or of one staker, into a single value.
+//!
+//! Each view is serialized as its fields, in the order they are declared. Fields are only ever
+//! appended, so clients reading a prefix of the fields keep working when new ones are added.
+use crate::address::Address;
+use alloc::{string::String, vec::Vec};
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    CLType, CLTyped, ContractPackageHash, U256,
+};
+
+// Declares a view: a struct whose byte representation is its fields, in order.
+macro_rules! info_struct {
+    ($(#[$meta:meta])* $name:ident { $($(#[$field_meta:meta])* $field:ident: $ty:ty,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct $name {
+            $($(#[$field_meta])* pub $field: $ty,)*
+        }
+
+        impl CLTyped for $name {
+            fn cl_type() -> CLType {
+                CLType::Any
+            }
+        }
+
+        impl ToBytes for $name {
+            fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+                let mut result = bytesrepr::allocate_buffer(self)?;
+                $(result.append(&mut self.$field.to_bytes()?);)*
+                Ok(result)
+            }
+
+            fn serialized_length(&self) -> usize {
+                0 $(+ self.$field.serialized_length())*
+            }
+        }
+
+        impl FromBytes for $name {
+            fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+                $(let ($field, bytes) = <$ty>::from_bytes(bytes)?;)*
+                Ok((Self { $($field,)* }, bytes))
+            }
+        }
+    };
+}
+
+info_struct!(
+    /// The whole state of the pool.
+    PoolInfo {
+        /// The name of the contract.
+        name: String,
+        /// The address of the contract.
+        address: String,
+        /// The package hash of the staked token.
+        erc20_contract_package_hash: ContractPackageHash,
+        /// The package hash of the token rewards are paid in.
+        reward_token_package_hash: ContractPackageHash,
+        /// The start of the staking window.
+        staking_starts: u64,
+        /// The end of the staking window.
+        staking_ends: u64,
+        /// The start of the withdraw window.
+        withdraw_starts: u64,
+        /// The end of the withdraw window.
+        withdraw_ends: u64,
+        /// The largest amount that can be staked.
+        staking_total: U256,
+        /// The reward added so far.
+        total_reward: U256,
+        /// The part of the reward paid out to early withdrawals.
+        early_withdraw_reward: U256,
+        /// The amount staked over the staking window.
+        staked_total: U256,
+        /// The reward left to pay out.
+        reward_balance: U256,
+        /// The amount staked and not withdrawn yet.
+        staked_balance: U256,
+        /// The weight staked over the staking window.
+        weighted_total: U256,
+        /// The weight staked and not withdrawn yet.
+        weighted_balance: U256,
+        /// 0 when the reward is shared over the fixed windows, 1 when it streams.
+        pool_mode: u8,
+        /// The reward streamed per second.
+        reward_rate: U256,
+        /// The reward streamed per unit of weight so far, scaled up.
+        acc_reward_per_share: U256,
+        /// The last time the streamed reward was accounted for.
+        last_reward_time: u64,
+        /// Whether the contract is paused.
+        paused: bool,
+        /// The penalty of early withdrawals, in basis points.
+        early_withdraw_penalty: u32,
+        /// The address early withdraw penalties are sent to.
+        penalty_treasury: Option<Address>,
+        /// The symbol of the receipts.
+        symbol: String,
+        /// The decimals of the receipts.
+        decimals: u8,
+        /// The number of receipts in circulation.
+        total_supply: U256,
+        /// The smallest amount a single stake can add.
+        min_stake: U256,
+        /// The largest amount an address can have staked, zero meaning no limit.
+        max_stake_per_address: U256,
+        /// Whether only allowlisted addresses can stake.
+        allowlist_enabled: bool,
+        /// The root of the Merkle tree of allowlisted addresses.
+        merkle_root: Option<[u8; 32]>,
+        /// The number of addresses that ever staked.
+        staker_count: u64,
+        /// The id of the current epoch.
+        current_epoch: u64,
+    }
+);
+
+info_struct!(
+    /// The state of the stake of one address.
+    StakerInfo {
+        /// The amount staked.
+        amount_staked: U256,
+        /// The reward the staker would get by withdrawing or claiming now.
+        reward: U256,
+        /// The weight of the stake in the reward formulas.
+        weight: U256,
+        /// The time until which the stake is locked.
+        unlock_time: u64,
+        /// The reward claimed over the fixed windows without withdrawing.
+        claimed_reward: U256,
+        /// The positions of the staker, as (amount, withdrawn) tuples.
+        positions: Vec<(U256, U256)>,
+        /// The epoch the stake belongs to.
+        epoch: u64,
+    }
+);
+
+info_struct!(
+    /// The windows, the cap and the reward accounting of one epoch.
+    EpochInfo {
+        /// The start of the staking window.
+        staking_starts: u64,
+        /// The end of the staking window.
+        staking_ends: u64,
+        /// The start of the withdraw window.
+        withdraw_starts: u64,
+        /// The end of the withdraw window.
+        withdraw_ends: u64,
+        /// The largest amount that can be staked.
+        staking_total: U256,
+        /// The reward added so far.
+        total_reward: U256,
+        /// The part of the reward paid out to early withdrawals.
+        early_withdraw_reward: U256,
+        /// The amount staked over the staking window.
+        staked_total: U256,
+        /// The reward left to pay out.
+        reward_balance: U256,
+        /// The amount staked and not withdrawn yet.
+        staked_balance: U256,
+        /// The weight staked over the staking window.
+        weighted_total: U256,
+        /// The weight staked and not withdrawn yet.
+        weighted_balance: U256,
+    }
+);
@@ -2,9 +2,15 @@
 extern crate alloc;
 
 pub mod address;
+pub mod ces;
 pub mod data;
 pub mod detail;
+pub mod epochs;
 pub mod error;
 pub mod event;
+pub mod info;
+pub mod merkle;
 pub mod modifiers;
+pub mod pools;
 pub mod staking_contract;
+pub mod staking_factory;
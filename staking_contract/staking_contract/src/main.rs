@@ -8,7 +8,7 @@ compile_error!("target arch should be wasm32: compile with '--target wasm32-unkn
 extern crate alloc;
 
 use alloc::vec;
-use alloc::{collections::BTreeSet, format, string::String};
+use alloc::{collections::BTreeSet, format, string::String, vec::Vec};
 
 // Contract API dependencies
 use casper_contract::{
@@ -16,27 +16,84 @@ use casper_contract::{
     unwrap_or_revert::UnwrapOrRevert,
 };
 use casper_types::{
-    runtime_args, CLTyped, CLValue, ContractPackageHash, EntryPoint, EntryPointAccess,
-    EntryPointType, EntryPoints, Group, Key, Parameter, RuntimeArgs, URef, U256,
+    contracts::NamedKeys, runtime_args, ApiError, CLTyped, CLValue, ContractHash,
+    ContractPackageHash, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, Group, Key,
+    Parameter, RuntimeArgs, URef, U256,
 };
 
 // Custom dependencies
-use contract_utils::{ContractContext, OnChainContractStorage};
-use staking_contract::staking_contract::CEP20STK;
+use contract_utils::{AdminControl, ContractContext, OnChainContractStorage};
+use staking_contract::{
+    address::Address,
+    detail,
+    error::Error,
+    info::{EpochInfo, PoolInfo, StakerInfo},
+    staking_contract::CEP20STK,
+};
 
 const ENTRY_POINT_NAME: &str = "name";
 const ENTRY_POINT_ADDRESS: &str = "address";
 const ENTRY_POINT_STAKING_STARTS: &str = "staking_starts";
+const ENTRY_POINT_STAKING_ENDS: &str = "staking_ends";
 const ENTRY_POINT_WITHDRAW_STARTS: &str = "withdraw_starts";
 const ENTRY_POINT_WITHDRAW_ENDS: &str = "withdraw_ends";
 const ENTRY_POINT_STAKING_TOTAL: &str = "staking_total";
 const ENTRY_POINT_WITHDRAW: &str = "withdraw";
 const ENTRY_POINT_STAKE: &str = "stake";
+const ENTRY_POINT_STAKE_FOR: &str = "stake_for";
+const ENTRY_POINT_TRANSFER_STAKE: &str = "transfer_stake";
 const ENTRY_POINT_ADD_REWARD: &str = "add_reward";
 const ENTRY_POINT_AMOUNT_STAKED: &str = "amount_staked";
 const ENTRY_POINT_GET_CURRENT_REWARD: &str = "get_current_reward";
 const ENTRY_POINT_CONSTRUCTOR: &str = "constructor";
 const ENTRY_POINT_STAKER_REWARD: &str = "staker_reward";
+const ENTRY_POINT_ADD_ADMIN: &str = "add_admin";
+const ENTRY_POINT_DISABLE_ADMIN: &str = "disable_admin";
+const ENTRY_POINT_SET_STAKING_TOTAL: &str = "set_staking_total";
+const ENTRY_POINT_SET_STAKING_ENDS: &str = "set_staking_ends";
+const ENTRY_POINT_SET_WITHDRAW_ENDS: &str = "set_withdraw_ends";
+const ENTRY_POINT_POOL_MODE: &str = "pool_mode";
+const ENTRY_POINT_REWARD_RATE: &str = "reward_rate";
+const ENTRY_POINT_SET_REWARD_RATE: &str = "set_reward_rate";
+const ENTRY_POINT_CLAIM_REWARDS: &str = "claim_rewards";
+const ENTRY_POINT_COMPOUND: &str = "compound";
+const ENTRY_POINT_PAUSED: &str = "paused";
+const ENTRY_POINT_PAUSE: &str = "pause";
+const ENTRY_POINT_UNPAUSE: &str = "unpause";
+const ENTRY_POINT_EMERGENCY_WITHDRAW: &str = "emergency_withdraw";
+const ENTRY_POINT_LOCK_TIER: &str = "lock_tier";
+const ENTRY_POINT_SET_LOCK_TIER: &str = "set_lock_tier";
+const ENTRY_POINT_UNLOCK_TIME: &str = "unlock_time";
+const ENTRY_POINT_POSITIONS: &str = "positions";
+const ENTRY_POINT_WITHDRAW_POSITION: &str = "withdraw_position";
+const ENTRY_POINT_SYMBOL: &str = "symbol";
+const ENTRY_POINT_DECIMALS: &str = "decimals";
+const ENTRY_POINT_TOTAL_SUPPLY: &str = "total_supply";
+const ENTRY_POINT_BALANCE_OF: &str = "balance_of";
+const ENTRY_POINT_ALLOWANCE: &str = "allowance";
+const ENTRY_POINT_APPROVE: &str = "approve";
+const ENTRY_POINT_TRANSFER: &str = "transfer";
+const ENTRY_POINT_TRANSFER_FROM: &str = "transfer_from";
+const ENTRY_POINT_ALLOWLIST_ENABLED: &str = "allowlist_enabled";
+const ENTRY_POINT_SET_ALLOWLIST_ENABLED: &str = "set_allowlist_enabled";
+const ENTRY_POINT_ALLOWLISTED: &str = "allowlisted";
+const ENTRY_POINT_ADD_TO_ALLOWLIST: &str = "add_to_allowlist";
+const ENTRY_POINT_REMOVE_FROM_ALLOWLIST: &str = "remove_from_allowlist";
+const ENTRY_POINT_SET_MERKLE_ROOT: &str = "set_merkle_root";
+const ENTRY_POINT_MIN_STAKE: &str = "min_stake";
+const ENTRY_POINT_MAX_STAKE_PER_ADDRESS: &str = "max_stake_per_address";
+const ENTRY_POINT_SET_STAKE_LIMITS: &str = "set_stake_limits";
+const ENTRY_POINT_STAKER_COUNT: &str = "staker_count";
+const ENTRY_POINT_STAKERS: &str = "stakers";
+const ENTRY_POINT_POOL_INFO: &str = "pool_info";
+const ENTRY_POINT_STAKER_INFO: &str = "staker_info";
+const ENTRY_POINT_MIGRATE: &str = "migrate";
+const ENTRY_POINT_SCHEMA_VERSION: &str = "schema_version";
+const ENTRY_POINT_CURRENT_EPOCH: &str = "current_epoch";
+const ENTRY_POINT_STAKER_EPOCH: &str = "staker_epoch";
+const ENTRY_POINT_EPOCH_INFO: &str = "epoch_info";
+const ENTRY_POINT_SCHEDULE_EPOCH: &str = "schedule_epoch";
+const ENTRY_POINT_ROLL_STAKE: &str = "roll_stake";
 
 const AMOUNT: &str = "amount";
 const STAKER: &str = "staker";
@@ -49,11 +106,34 @@ const WITHDRAW_ENDS: &str = "withdraw_ends";
 const STAKING_TOTAL: &str = "staking_total";
 const STACKING_CONTRACT_PACKAGE_HASH: &str = "stacking_contract_package_hash";
 const ERC20_CONTRACT_PACKAGE_HASH: &str = "erc20_contract_package_hash";
+const REWARD_TOKEN_PACKAGE_HASH: &str = "reward_token_package_hash";
 const STAKER_ADDRESS: &str = "staker_address";
 const WITHDRAWABLE_AMOUNT: &str = "withdrawable_amount";
 const REWARD_AMOUNT: &str = "reward_amount";
-const CONTRACT_PACKAGE_HASH: &str = "contract_package_hash";
+const CONTRACT_PACKAGE_HASH: &str = "staking_contract_package_hash";
+const CONTRACT_ACCESS_UREF: &str = "staking_contract_access_uref";
 const STAKING_CONTRACT_HASH: &str = "staking_contract_hash";
+const POOL_MODE: &str = "pool_mode";
+const REWARD_RATE: &str = "reward_rate";
+const EARLY_WITHDRAW_PENALTY: &str = "early_withdraw_penalty";
+const PENALTY_TREASURY: &str = "penalty_treasury";
+const LOCK_DURATION: &str = "lock_duration";
+const MULTIPLIER: &str = "multiplier";
+const POSITION_ID: &str = "position_id";
+const BENEFICIARY: &str = "beneficiary";
+const RECIPIENT: &str = "recipient";
+const SYMBOL: &str = "symbol";
+const OWNER: &str = "owner";
+const SPENDER: &str = "spender";
+const MERKLE_PROOF: &str = "merkle_proof";
+const MERKLE_ROOT: &str = "merkle_root";
+const ENABLED: &str = "enabled";
+const STAKERS: &str = "stakers";
+const MIN_STAKE: &str = "min_stake";
+const MAX_STAKE_PER_ADDRESS: &str = "max_stake_per_address";
+const OFFSET: &str = "offset";
+const LIMIT: &str = "limit";
+const EPOCH: &str = "epoch";
 
 const CONSTRUCTOR_GROUP: &str = "constructor";
 
@@ -69,6 +149,10 @@ impl ContractContext<OnChainContractStorage> for Staking {
 
 impl CEP20STK<OnChainContractStorage> for Staking {}
 
+impl AdminControl<OnChainContractStorage> for Staking {
+    const NOT_AN_ADMIN: ApiError = ApiError::User(Error::NotAnAdmin as u16);
+}
+
 impl Staking {
     /// Constructor for the token contract.
     #[allow(clippy::too_many_arguments)]
@@ -81,6 +165,13 @@ impl Staking {
         withdraw_starts: u64,
         withdraw_ends: u64,
         staking_total: U256,
+        pool_mode: u8,
+        reward_rate: U256,
+        early_withdraw_penalty: u32,
+        penalty_treasury: Option<Address>,
+        symbol: String,
+        min_stake: U256,
+        max_stake_per_address: U256,
     ) {
         CEP20STK::init(
             self,
@@ -91,8 +182,19 @@ impl Staking {
             withdraw_starts,
             withdraw_ends,
             staking_total,
+            pool_mode,
+            reward_rate,
+            early_withdraw_penalty,
+            penalty_treasury,
+            symbol,
+            min_stake,
+            max_stake_per_address,
         )
         .unwrap_or_revert();
+        // The installer becomes the first admin.
+        AdminControl::init(self);
+        let installer = self.get_caller();
+        self.add_admin_without_checked(installer);
     }
 }
 
@@ -104,6 +206,14 @@ impl Staking {
 // - withdraw_starts: The start date of the withdrawal period.
 // - withdraw_ends: The end date of the withdrawal period.
 // - staking_total: The total number of tokens that can be staked.
+// - reward_token_package_hash: The package hash of the token rewards are paid in, if it differs from the staked token.
+// - pool_mode: 0 to share the reward over the fixed windows, 1 to stream it every second.
+// - reward_rate: The reward streamed per second in streaming mode.
+// - early_withdraw_penalty: The penalty, in basis points, taken out of the principal of early withdrawals.
+// - penalty_treasury: The address penalties are sent to, or none to add them to the reward balance.
+// - symbol: The symbol of the receipts minted for the staked tokens.
+// - min_stake: The smallest amount a single stake can add.
+// - max_stake_per_address: The largest amount an address can have staked, 0 for no limit.
 #[no_mangle]
 pub extern "C" fn constructor() {
     // Read arguments for the constructor call.
@@ -117,6 +227,15 @@ pub extern "C" fn constructor() {
     let stacking_contract_package_hash =
         runtime::get_named_arg::<Key>(STACKING_CONTRACT_PACKAGE_HASH);
     let erc20_contract_package_hash = runtime::get_named_arg::<Key>(ERC20_CONTRACT_PACKAGE_HASH);
+    let reward_token_package_hash =
+        runtime::get_named_arg::<Option<Key>>(REWARD_TOKEN_PACKAGE_HASH);
+    let pool_mode: u8 = runtime::get_named_arg::<u8>(POOL_MODE);
+    let reward_rate: U256 = runtime::get_named_arg::<U256>(REWARD_RATE);
+    let early_withdraw_penalty: u32 = runtime::get_named_arg::<u32>(EARLY_WITHDRAW_PENALTY);
+    let penalty_treasury = runtime::get_named_arg::<Option<Address>>(PENALTY_TREASURY);
+    let symbol = runtime::get_named_arg::<String>(SYMBOL);
+    let min_stake: U256 = runtime::get_named_arg::<U256>(MIN_STAKE);
+    let max_stake_per_address: U256 = runtime::get_named_arg::<U256>(MAX_STAKE_PER_ADDRESS);
 
     // Store the stacking_contract_package_hash and erc20_contract_package_hash as keys
     #[allow(clippy::useless_conversion)]
@@ -131,6 +250,11 @@ pub extern "C" fn constructor() {
         erc20_contract_package_hash.into(),
     );
 
+    // Without a reward token, rewards are paid in the staked token
+    if let Some(reward_token_package_hash) = reward_token_package_hash {
+        runtime::put_key(REWARD_TOKEN_PACKAGE_HASH, reward_token_package_hash);
+    }
+
     // Initialize the token contract using the constructor arguments
     Staking::default().constructor(
         name,
@@ -140,6 +264,13 @@ pub extern "C" fn constructor() {
         withdraw_starts,
         withdraw_ends,
         staking_total,
+        pool_mode,
+        reward_rate,
+        early_withdraw_penalty,
+        penalty_treasury,
+        symbol,
+        min_stake,
+        max_stake_per_address,
     );
 }
 
@@ -167,7 +298,7 @@ pub extern "C" fn staking_starts() {
 // The `staking_ends` function returns the end date of the staking period.
 #[no_mangle]
 pub extern "C" fn staking_ends() {
-    let ret = Staking::default().staking_starts();
+    let ret = Staking::default().staking_ends();
     runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
 }
 
@@ -192,6 +323,20 @@ pub extern "C" fn staking_total() {
     runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
 }
 
+// The `pool_mode` function returns 0 for a fixed window pool and 1 for a streaming pool.
+#[no_mangle]
+pub extern "C" fn pool_mode() {
+    let ret = Staking::default().pool_mode();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `reward_rate` function returns the reward streamed per second in streaming mode.
+#[no_mangle]
+pub extern "C" fn reward_rate() {
+    let ret = Staking::default().reward_rate();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
 // The `amount_staked` function takes the following argument:
 // - staker: The address of the staker.
 // The function returns the number of tokens that the staker has staked.
@@ -204,14 +349,139 @@ pub extern "C" fn amount_staked() {
 
 // The `stake` function takes the following arguments:
 // - amount: The number of tokens to stake.
+// - lock_duration: Optional, the number of milliseconds the stake is locked for. It must match a lock tier.
+// - merkle_proof: Optional, the proof that the caller is in the Merkle tree of the allowlist.
 // The function stakes the specified number of tokens in the staking contract.
 #[no_mangle]
 pub extern "C" fn stake() {
     let amount = runtime::get_named_arg::<U256>(AMOUNT);
-    let ret = Staking::default().stake(amount).unwrap_or_revert();
+    let lock_duration = detail::get_optional_named_arg::<u64>(LOCK_DURATION).unwrap_or_default();
+    let merkle_proof =
+        detail::get_optional_named_arg::<Vec<[u8; 32]>>(MERKLE_PROOF).unwrap_or_default();
+    let ret = Staking::default()
+        .stake(amount, lock_duration, merkle_proof)
+        .unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `stake_for` function takes the following arguments:
+// - beneficiary: The address the stake is credited to.
+// - amount: The number of tokens to stake, taken from the caller.
+// - lock_duration: Optional, the number of milliseconds the stake is locked for. It must match a lock tier.
+// - merkle_proof: Optional, the proof that the beneficiary is in the Merkle tree of the allowlist.
+// The function stakes the specified number of tokens of the caller on behalf of the beneficiary.
+#[no_mangle]
+pub extern "C" fn stake_for() {
+    let beneficiary = runtime::get_named_arg::<Address>(BENEFICIARY);
+    let amount = runtime::get_named_arg::<U256>(AMOUNT);
+    let lock_duration = detail::get_optional_named_arg::<u64>(LOCK_DURATION).unwrap_or_default();
+    let merkle_proof =
+        detail::get_optional_named_arg::<Vec<[u8; 32]>>(MERKLE_PROOF).unwrap_or_default();
+    let ret = Staking::default()
+        .stake_for(beneficiary, amount, lock_duration, merkle_proof)
+        .unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `transfer_stake` function takes the following arguments:
+// - recipient: The address the stake is moved to.
+// - amount: The number of staked tokens to move.
+// The function moves part or all of the caller's stake to the recipient, without moving any tokens.
+#[no_mangle]
+pub extern "C" fn transfer_stake() {
+    let recipient = runtime::get_named_arg::<Address>(RECIPIENT);
+    let amount = runtime::get_named_arg::<U256>(AMOUNT);
+    let ret = Staking::default()
+        .transfer_stake(recipient, amount)
+        .unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `symbol` function returns the symbol of the receipts.
+#[no_mangle]
+pub extern "C" fn symbol() {
+    let ret = Staking::default().symbol();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `decimals` function returns the decimals of the receipts, which are the ones of the staked
+// token.
+#[no_mangle]
+pub extern "C" fn decimals() {
+    let ret = Staking::default().decimals();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `total_supply` function returns the number of receipts in circulation.
+#[no_mangle]
+pub extern "C" fn total_supply() {
+    let ret = Staking::default().total_supply();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `balance_of` function takes the following arguments:
+// - address: The address to return the receipts of.
+// The function returns the receipts held by the address, which is the amount it has staked.
+#[no_mangle]
+pub extern "C" fn balance_of() {
+    let owner = runtime::get_named_arg::<Key>(ADDRESS);
+    let ret = Staking::default().balance_of(owner);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `allowance` function takes the following arguments:
+// - owner: The address holding the receipts.
+// - spender: The address allowed to move them.
+// The function returns the receipts the owner allows the spender to move.
+#[no_mangle]
+pub extern "C" fn allowance() {
+    let owner = runtime::get_named_arg::<Key>(OWNER);
+    let spender = runtime::get_named_arg::<Key>(SPENDER);
+    let ret = Staking::default().allowance(owner, spender);
     runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
 }
 
+// The `approve` function takes the following arguments:
+// - spender: The address allowed to move the receipts.
+// - amount: The number of receipts of the caller it may move.
+#[no_mangle]
+pub extern "C" fn approve() {
+    let spender = runtime::get_named_arg::<Key>(SPENDER);
+    let amount = runtime::get_named_arg::<U256>(AMOUNT);
+    Staking::default()
+        .approve(spender, amount)
+        .unwrap_or_revert();
+}
+
+// The `transfer` function takes the following arguments:
+// - recipient: The address the receipts are sent to.
+// - amount: The number of receipts to send.
+// The function sends receipts of the caller, and the stake behind them, to the recipient.
+#[no_mangle]
+pub extern "C" fn transfer() {
+    let recipient = runtime::get_named_arg::<Address>(RECIPIENT);
+    let amount = runtime::get_named_arg::<U256>(AMOUNT);
+    Staking::default()
+        .transfer(recipient, amount)
+        .unwrap_or_revert();
+}
+
+// The `transfer_from` function takes the following arguments:
+// - owner: The address holding the receipts.
+// - recipient: The address the receipts are sent to.
+// - amount: The number of receipts to send.
+// The function sends receipts of the owner, and the stake behind them, to the recipient, using the
+// allowance the owner gave to the caller.
+#[no_mangle]
+pub extern "C" fn transfer_from() {
+    let owner = runtime::get_named_arg::<Address>(OWNER);
+    let recipient = runtime::get_named_arg::<Address>(RECIPIENT);
+    let amount = runtime::get_named_arg::<U256>(AMOUNT);
+    Staking::default()
+        .transfer_from(owner, recipient, amount)
+        .unwrap_or_revert();
+}
+
 // The `withdraw` function takes the following arguments:
 // - amount: The number of tokens to withdraw.
 // The function withdraws the specified number of tokens from the staking contract.
@@ -222,6 +492,198 @@ pub extern "C" fn withdraw() {
     runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
 }
 
+// The `claim_rewards` function pays out the reward the caller has earned so far and keeps their
+// stake in place. The function returns the claimed reward.
+#[no_mangle]
+pub extern "C" fn claim_rewards() {
+    let ret = Staking::default().claim_rewards().unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `compound` function adds the reward the caller has earned so far to their stake. It is only
+// available when rewards are paid in the staked token. The function returns the compounded reward.
+#[no_mangle]
+pub extern "C" fn compound() {
+    let ret = Staking::default().compound().unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `lock_tier` function takes the following argument:
+// - lock_duration: The number of milliseconds a stake is locked for.
+// The function returns the reward multiplier of the lock duration in basis points, or 0 if there is no such tier.
+#[no_mangle]
+pub extern "C" fn lock_tier() {
+    let lock_duration = runtime::get_named_arg::<u64>(LOCK_DURATION);
+    let ret = Staking::default().lock_tier(lock_duration);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `unlock_time` function takes the following argument:
+// - staker: The address of the staker.
+// The function returns the time until which the stake of the staker is locked.
+#[no_mangle]
+pub extern "C" fn unlock_time() {
+    let staker = runtime::get_named_arg::<Key>(STAKER);
+    let ret = Staking::default().unlock_time(staker);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `set_lock_tier` function takes the following arguments:
+// - lock_duration: The number of milliseconds a stake is locked for.
+// - multiplier: The reward multiplier of the lock duration in basis points, 10000 being 1x. 0 closes the tier.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn set_lock_tier() {
+    let lock_duration = runtime::get_named_arg::<u64>(LOCK_DURATION);
+    let multiplier = runtime::get_named_arg::<u32>(MULTIPLIER);
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    staking
+        .set_lock_tier(lock_duration, multiplier)
+        .unwrap_or_revert();
+}
+
+// The `emergency_withdraw` function returns the caller's whole stake and gives up their reward.
+// It works even while the contract is paused. The function returns the withdrawn amount.
+#[no_mangle]
+pub extern "C" fn emergency_withdraw() {
+    let ret = Staking::default().emergency_withdraw().unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `paused` function returns whether stakes, rewards and reward payouts are stopped.
+#[no_mangle]
+pub extern "C" fn paused() {
+    let ret = Staking::default().paused();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `allowlist_enabled` function returns whether only allowlisted addresses can stake.
+#[no_mangle]
+pub extern "C" fn allowlist_enabled() {
+    let ret = Staking::default().allowlist_enabled();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `set_allowlist_enabled` function takes the following argument:
+// - enabled: Whether only allowlisted addresses can stake.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn set_allowlist_enabled() {
+    let enabled = runtime::get_named_arg::<bool>(ENABLED);
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    staking.set_allowlist_enabled(enabled);
+}
+
+// The `allowlisted` function takes the following argument:
+// - staker: The address of the staker.
+// The function returns whether the staker is on the allowlist.
+#[no_mangle]
+pub extern "C" fn allowlisted() {
+    let staker = runtime::get_named_arg::<Key>(STAKER);
+    let ret = Staking::default().allowlisted(staker);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `add_to_allowlist` function takes the following argument:
+// - stakers: The addresses to add to the allowlist.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn add_to_allowlist() {
+    let stakers = runtime::get_named_arg::<Vec<Key>>(STAKERS);
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    staking.add_to_allowlist(stakers);
+}
+
+// The `remove_from_allowlist` function takes the following argument:
+// - stakers: The addresses to remove from the allowlist.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn remove_from_allowlist() {
+    let stakers = runtime::get_named_arg::<Vec<Key>>(STAKERS);
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    staking.remove_from_allowlist(stakers);
+}
+
+// The `set_merkle_root` function takes the following argument:
+// - merkle_root: The root of the Merkle tree of allowlisted addresses, or none to only use the list.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn set_merkle_root() {
+    let merkle_root = runtime::get_named_arg::<Option<[u8; 32]>>(MERKLE_ROOT);
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    staking.set_merkle_root(merkle_root);
+}
+
+// The `pause` function stops stakes, rewards and reward payouts.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn pause() {
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    staking.pause().unwrap_or_revert();
+}
+
+// The `unpause` function resumes stakes, rewards and reward payouts.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn unpause() {
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    staking.unpause().unwrap_or_revert();
+}
+
+// The `positions` function takes the following argument:
+// - staker: The address of the staker.
+// The function returns the positions of the staker, indexed by position id, as (amount, withdrawn) tuples.
+#[no_mangle]
+pub extern "C" fn positions() {
+    let staker = runtime::get_named_arg::<Key>(STAKER);
+    let ret: Vec<(U256, U256)> = Staking::default()
+        .positions(staker)
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `staker_count` function returns the number of addresses that ever staked.
+#[no_mangle]
+pub extern "C" fn staker_count() {
+    let ret = Staking::default().staker_count();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `stakers` function takes the following arguments:
+// - offset: The index of the first staker to return, in the order they first staked.
+// - limit: The largest number of stakers to return.
+// The function returns (staker, amount staked) pairs, with a zero amount for stakers that left.
+#[no_mangle]
+pub extern "C" fn stakers() {
+    let offset = runtime::get_named_arg::<u64>(OFFSET);
+    let limit = runtime::get_named_arg::<u64>(LIMIT);
+    let ret = Staking::default().stakers(offset, limit);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `withdraw_position` function takes the following arguments:
+// - position_id: The id of the position to withdraw from.
+// - amount: The number of tokens to withdraw.
+// The function withdraws the specified number of tokens from one position of the caller.
+#[no_mangle]
+pub extern "C" fn withdraw_position() {
+    let position_id = runtime::get_named_arg::<u64>(POSITION_ID);
+    let amount = runtime::get_named_arg::<U256>(AMOUNT);
+    let ret = Staking::default()
+        .withdraw_position(position_id, amount)
+        .unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
 // The `add_reward` function takes the following arguments:
 // - reward_amount: The amount of reward to add.
 // - withdrawable_amount: The amount of reward that is now withdrawable.
@@ -230,7 +692,9 @@ pub extern "C" fn withdraw() {
 pub extern "C" fn add_reward() {
     let reward_amount = runtime::get_named_arg::<U256>(REWARD_AMOUNT);
     let withdrawable_amount = runtime::get_named_arg::<U256>(WITHDRAWABLE_AMOUNT);
-    let ret = Staking::default()
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    let ret = staking
         .add_reward(reward_amount, withdrawable_amount)
         .unwrap_or_revert();
     runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
@@ -255,8 +719,216 @@ pub extern "C" fn staker_reward() {
     runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
 }
 
+// The `pool_info` function returns the whole state of the pool as a single value.
+#[no_mangle]
+pub extern "C" fn pool_info() {
+    let ret = Staking::default().pool_info();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `staker_info` function takes the following argument:
+// - staker: The address of the staker.
+// The function returns the amount staked, the current reward and the positions of the staker as a
+// single value.
+#[no_mangle]
+pub extern "C" fn staker_info() {
+    let staker = runtime::get_named_arg::<Key>(STAKER);
+    let ret = Staking::default().staker_info(staker).unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `add_admin` function takes the following argument:
+// - address: The account or contract that becomes an admin.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn add_admin() {
+    let address = runtime::get_named_arg::<Key>(ADDRESS);
+    Staking::default().add_admin(address);
+}
+
+// The `disable_admin` function takes the following argument:
+// - address: The account or contract that loses admin rights.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn disable_admin() {
+    let address = runtime::get_named_arg::<Key>(ADDRESS);
+    Staking::default().disable_admin(address);
+}
+
+// The `set_staking_total` function takes the following argument:
+// - staking_total: The new total number of tokens that can be staked.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn set_staking_total() {
+    let staking_total = runtime::get_named_arg::<U256>(STAKING_TOTAL);
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    staking
+        .update_staking_total(staking_total)
+        .unwrap_or_revert();
+}
+
+// The `min_stake` function returns the smallest amount a single stake can add.
+#[no_mangle]
+pub extern "C" fn min_stake() {
+    let ret = Staking::default().min_stake();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `max_stake_per_address` function returns the largest amount an address can have staked, or
+// 0 if there is no limit.
+#[no_mangle]
+pub extern "C" fn max_stake_per_address() {
+    let ret = Staking::default().max_stake_per_address();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `set_stake_limits` function takes the following arguments:
+// - min_stake: The smallest amount a single stake can add.
+// - max_stake_per_address: The largest amount an address can have staked, 0 for no limit.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn set_stake_limits() {
+    let min_stake = runtime::get_named_arg::<U256>(MIN_STAKE);
+    let max_stake_per_address = runtime::get_named_arg::<U256>(MAX_STAKE_PER_ADDRESS);
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    staking
+        .update_stake_limits(min_stake, max_stake_per_address)
+        .unwrap_or_revert();
+}
+
+// The `set_staking_ends` function takes the following argument:
+// - staking_ends: The new end date of the staking period, which is also the start of the withdrawal period.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn set_staking_ends() {
+    let staking_ends = runtime::get_named_arg::<u64>(STAKING_ENDS);
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    staking.update_staking_ends(staking_ends).unwrap_or_revert();
+}
+
+// The `set_withdraw_ends` function takes the following argument:
+// - withdraw_ends: The new end date of the withdrawal period.
+// Only an admin can call this function.
+#[no_mangle]
+pub extern "C" fn set_withdraw_ends() {
+    let withdraw_ends = runtime::get_named_arg::<u64>(WITHDRAW_ENDS);
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    staking
+        .update_withdraw_ends(withdraw_ends)
+        .unwrap_or_revert();
+}
+
+// The `set_reward_rate` function takes the following argument:
+// - reward_rate: The new reward streamed per second.
+// Only an admin can call this function, and only in streaming mode.
+#[no_mangle]
+pub extern "C" fn set_reward_rate() {
+    let reward_rate = runtime::get_named_arg::<U256>(REWARD_RATE);
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    staking.update_reward_rate(reward_rate).unwrap_or_revert();
+}
+
+// The `migrate` function carries the state written by the previous version of the contract forward
+// to this one. The installer calls it when upgrading the package. Only an admin can call this
+// function.
+#[no_mangle]
+pub extern "C" fn migrate() {
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    staking.migrate().unwrap_or_revert();
+}
+
+// The `schema_version` function returns the version of the layout of the stored state.
+#[no_mangle]
+pub extern "C" fn schema_version() {
+    let ret = Staking::default().schema_version();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `current_epoch` function returns the id of the current epoch, starting at 0.
+#[no_mangle]
+pub extern "C" fn current_epoch() {
+    let ret = Staking::default().current_epoch();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `staker_epoch` function takes the following argument:
+// - staker: The address of the staker.
+// The function returns the id of the epoch the stake of the staker belongs to.
+#[no_mangle]
+pub extern "C" fn staker_epoch() {
+    let staker = runtime::get_named_arg::<Key>(STAKER);
+    let ret = Staking::default().staker_epoch(staker);
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `epoch_info` function takes the following argument:
+// - epoch: The id of the epoch.
+// The function returns the windows, the staking cap and the reward accounting of the epoch as a
+// single value.
+#[no_mangle]
+pub extern "C" fn epoch_info() {
+    let epoch = runtime::get_named_arg::<u64>(EPOCH);
+    let ret = Staking::default().epoch_info(epoch).unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `schedule_epoch` function takes the following arguments:
+// - staking_starts: The start date of the staking period of the new epoch.
+// - staking_ends: The end date of the staking period of the new epoch.
+// - withdraw_starts: The start date of the withdrawal period of the new epoch.
+// - withdraw_ends: The end date of the withdrawal period of the new epoch.
+// - staking_total: The staking cap of the new epoch.
+// The function returns the id of the new epoch. Only an admin can call this function, once the
+// withdrawal period of the current epoch has ended.
+#[no_mangle]
+pub extern "C" fn schedule_epoch() {
+    let staking_starts = runtime::get_named_arg::<u64>(STAKING_STARTS);
+    let staking_ends = runtime::get_named_arg::<u64>(STAKING_ENDS);
+    let withdraw_starts = runtime::get_named_arg::<u64>(WITHDRAW_STARTS);
+    let withdraw_ends = runtime::get_named_arg::<u64>(WITHDRAW_ENDS);
+    let staking_total = runtime::get_named_arg::<U256>(STAKING_TOTAL);
+    let mut staking = Staking::default();
+    staking.assert_caller_is_admin();
+    let ret = staking
+        .schedule_epoch(
+            staking_starts,
+            staking_ends,
+            withdraw_starts,
+            withdraw_ends,
+            staking_total,
+        )
+        .unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
+// The `roll_stake` function moves the stake of the caller from a past epoch into the current one
+// and pays out the reward it earned there. The function returns the amount rolled over.
+// - merkle_proof: Optional, the proof that the caller is in the Merkle tree of the allowlist.
+#[no_mangle]
+pub extern "C" fn roll_stake() {
+    let merkle_proof =
+        detail::get_optional_named_arg::<Vec<[u8; 32]>>(MERKLE_PROOF).unwrap_or_default();
+    let ret = Staking::default()
+        .roll_stake(merkle_proof)
+        .unwrap_or_revert();
+    runtime::ret(CLValue::from_t(ret).unwrap_or_revert());
+}
+
 #[no_mangle]
 pub extern "C" fn call() {
+    // An account that already installed the contract upgrades it instead of installing a second
+    // package.
+    if let Some(package_hash_key) = runtime::get_key(CONTRACT_PACKAGE_HASH) {
+        upgrade(package_hash_key);
+        return;
+    }
+
     // Read arguments for the constructor call.
     let name: String = runtime::get_named_arg(NAME);
     let address = runtime::get_named_arg::<String>(ADDRESS);
@@ -266,12 +938,27 @@ pub extern "C" fn call() {
     let withdraw_ends: u64 = runtime::get_named_arg::<u64>(WITHDRAW_ENDS);
     let staking_total: U256 = runtime::get_named_arg::<U256>(STAKING_TOTAL);
     let erc20_contract_package_hash = runtime::get_named_arg::<Key>(ERC20_CONTRACT_PACKAGE_HASH);
+    let reward_token_package_hash =
+        detail::get_optional_named_arg::<Key>(REWARD_TOKEN_PACKAGE_HASH);
+    // Pools share the reward over the fixed windows unless asked to stream it
+    let pool_mode = detail::get_optional_named_arg::<u8>(POOL_MODE).unwrap_or_default();
+    let reward_rate = detail::get_optional_named_arg::<U256>(REWARD_RATE).unwrap_or_default();
+    // Early withdrawals are not penalized unless asked to
+    let early_withdraw_penalty =
+        detail::get_optional_named_arg::<u32>(EARLY_WITHDRAW_PENALTY).unwrap_or_default();
+    let penalty_treasury = detail::get_optional_named_arg::<Address>(PENALTY_TREASURY);
+    // Receipts are named after the contract unless given a symbol
+    let symbol = detail::get_optional_named_arg::<String>(SYMBOL).unwrap_or_else(|| name.clone());
+    // Stakes are only bound by the staking total unless asked otherwise
+    let min_stake = detail::get_optional_named_arg::<U256>(MIN_STAKE).unwrap_or_default();
+    let max_stake_per_address =
+        detail::get_optional_named_arg::<U256>(MAX_STAKE_PER_ADDRESS).unwrap_or_default();
 
     let (contract_hash, _) = storage::new_contract(
         get_entry_points(),
         None,
         Some(String::from(CONTRACT_PACKAGE_HASH)),
-        None,
+        Some(String::from(CONTRACT_ACCESS_UREF)),
     );
 
     let package_hash: ContractPackageHash = ContractPackageHash::new(
@@ -294,6 +981,14 @@ pub extern "C" fn call() {
         STAKING_TOTAL => staking_total,
         STACKING_CONTRACT_PACKAGE_HASH => package_hash_key,
         ERC20_CONTRACT_PACKAGE_HASH => erc20_contract_package_hash,
+        REWARD_TOKEN_PACKAGE_HASH => reward_token_package_hash,
+        POOL_MODE => pool_mode,
+        REWARD_RATE => reward_rate,
+        EARLY_WITHDRAW_PENALTY => early_withdraw_penalty,
+        PENALTY_TREASURY => penalty_treasury,
+        SYMBOL => symbol,
+        MIN_STAKE => min_stake,
+        MAX_STAKE_PER_ADDRESS => max_stake_per_address,
     };
 
     let constructor_access: URef =
@@ -309,6 +1004,30 @@ pub extern "C" fn call() {
     storage::remove_contract_user_group_urefs(package_hash, CONSTRUCTOR_GROUP, urefs)
         .unwrap_or_revert();
 
+    put_contract_hash(contract_hash);
+}
+
+// Adds a new version of the contract to the installed package, then migrates the stored state.
+// The new version inherits the named keys of the current one, which hold the whole state, and the
+// current one is disabled so it can't write to that state anymore.
+fn upgrade(package_hash_key: Key) {
+    let package_hash = ContractPackageHash::new(package_hash_key.into_hash().unwrap_or_revert());
+    let previous_contract_hash = runtime::get_key(STAKING_CONTRACT_HASH)
+        .and_then(Key::into_hash)
+        .map(ContractHash::new)
+        .unwrap_or_revert();
+
+    let (contract_hash, _) =
+        storage::add_contract_version(package_hash, get_entry_points(), NamedKeys::new());
+    storage::disable_contract_version(package_hash, previous_contract_hash).unwrap_or_revert();
+
+    let _: () = runtime::call_contract(contract_hash, ENTRY_POINT_MIGRATE, runtime_args! {});
+
+    put_contract_hash(contract_hash);
+}
+
+// Stores the hash of the installed version of the contract under the installing account.
+fn put_contract_hash(contract_hash: ContractHash) {
     runtime::put_key(STAKING_CONTRACT_HASH, contract_hash.into());
     runtime::put_key(
         &format!("{contract_hash}_contract_hash_wrapped"),
@@ -329,6 +1048,14 @@ fn get_entry_points() -> EntryPoints {
             Parameter::new(WITHDRAW_ENDS, u64::cl_type()),
             Parameter::new(STAKING_TOTAL, U256::cl_type()),
             Parameter::new(ERC20_CONTRACT_PACKAGE_HASH, String::cl_type()),
+            Parameter::new(REWARD_TOKEN_PACKAGE_HASH, Option::<Key>::cl_type()),
+            Parameter::new(POOL_MODE, u8::cl_type()),
+            Parameter::new(REWARD_RATE, U256::cl_type()),
+            Parameter::new(EARLY_WITHDRAW_PENALTY, u32::cl_type()),
+            Parameter::new(PENALTY_TREASURY, Option::<Key>::cl_type()),
+            Parameter::new(SYMBOL, String::cl_type()),
+            Parameter::new(MIN_STAKE, U256::cl_type()),
+            Parameter::new(MAX_STAKE_PER_ADDRESS, U256::cl_type()),
         ],
         <()>::cl_type(),
         EntryPointAccess::Groups(vec![Group::new(CONSTRUCTOR_GROUP)]),
@@ -355,6 +1082,13 @@ fn get_entry_points() -> EntryPoints {
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_STAKING_ENDS,
+        vec![],
+        u64::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
     entry_points.add_entry_point(EntryPoint::new(
         ENTRY_POINT_WITHDRAW_STARTS,
         vec![],
@@ -385,25 +1119,314 @@ fn get_entry_points() -> EntryPoints {
     ));
     entry_points.add_entry_point(EntryPoint::new(
         ENTRY_POINT_STAKE,
-        vec![Parameter::new(AMOUNT, U256::cl_type())],
+        vec![
+            Parameter::new(AMOUNT, U256::cl_type()),
+            Parameter::new(LOCK_DURATION, u64::cl_type()),
+            Parameter::new(MERKLE_PROOF, Vec::<[u8; 32]>::cl_type()),
+        ],
         <()>::cl_type(),
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
     entry_points.add_entry_point(EntryPoint::new(
-        ENTRY_POINT_AMOUNT_STAKED,
-        vec![Parameter::new(STAKER, Key::cl_type())],
-        <()>::cl_type(),
+        ENTRY_POINT_STAKE_FOR,
+        vec![
+            Parameter::new(BENEFICIARY, Key::cl_type()),
+            Parameter::new(AMOUNT, U256::cl_type()),
+            Parameter::new(LOCK_DURATION, u64::cl_type()),
+            Parameter::new(MERKLE_PROOF, Vec::<[u8; 32]>::cl_type()),
+        ],
+        U256::cl_type(),
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
     entry_points.add_entry_point(EntryPoint::new(
-        ENTRY_POINT_ADD_REWARD,
+        ENTRY_POINT_TRANSFER_STAKE,
         vec![
-            Parameter::new(REWARD_AMOUNT, Key::cl_type()),
-            Parameter::new(WITHDRAWABLE_AMOUNT, Key::cl_type()),
+            Parameter::new(RECIPIENT, Key::cl_type()),
+            Parameter::new(AMOUNT, U256::cl_type()),
         ],
-        <()>::cl_type(),
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_SYMBOL,
+        vec![],
+        String::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_DECIMALS,
+        vec![],
+        u8::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_TOTAL_SUPPLY,
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_BALANCE_OF,
+        vec![Parameter::new(ADDRESS, Key::cl_type())],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_ALLOWANCE,
+        vec![
+            Parameter::new(OWNER, Key::cl_type()),
+            Parameter::new(SPENDER, Key::cl_type()),
+        ],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_APPROVE,
+        vec![
+            Parameter::new(SPENDER, Key::cl_type()),
+            Parameter::new(AMOUNT, U256::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_TRANSFER,
+        vec![
+            Parameter::new(RECIPIENT, Key::cl_type()),
+            Parameter::new(AMOUNT, U256::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_TRANSFER_FROM,
+        vec![
+            Parameter::new(OWNER, Key::cl_type()),
+            Parameter::new(RECIPIENT, Key::cl_type()),
+            Parameter::new(AMOUNT, U256::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_AMOUNT_STAKED,
+        vec![Parameter::new(STAKER, Key::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_CLAIM_REWARDS,
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_COMPOUND,
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_POSITIONS,
+        vec![Parameter::new(STAKER, Key::cl_type())],
+        Vec::<(U256, U256)>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_MIGRATE,
+        vec![],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_SCHEMA_VERSION,
+        vec![],
+        u32::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_POOL_INFO,
+        vec![],
+        PoolInfo::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_STAKER_INFO,
+        vec![Parameter::new(STAKER, Key::cl_type())],
+        StakerInfo::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_STAKER_COUNT,
+        vec![],
+        u64::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_STAKERS,
+        vec![
+            Parameter::new(OFFSET, u64::cl_type()),
+            Parameter::new(LIMIT, u64::cl_type()),
+        ],
+        Vec::<(Key, U256)>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_WITHDRAW_POSITION,
+        vec![
+            Parameter::new(POSITION_ID, u64::cl_type()),
+            Parameter::new(AMOUNT, U256::cl_type()),
+        ],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_LOCK_TIER,
+        vec![Parameter::new(LOCK_DURATION, u64::cl_type())],
+        u32::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_UNLOCK_TIME,
+        vec![Parameter::new(STAKER, Key::cl_type())],
+        u64::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_SET_LOCK_TIER,
+        vec![
+            Parameter::new(LOCK_DURATION, u64::cl_type()),
+            Parameter::new(MULTIPLIER, u32::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_MIN_STAKE,
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_MAX_STAKE_PER_ADDRESS,
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_SET_STAKE_LIMITS,
+        vec![
+            Parameter::new(MIN_STAKE, U256::cl_type()),
+            Parameter::new(MAX_STAKE_PER_ADDRESS, U256::cl_type()),
+        ],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_ALLOWLIST_ENABLED,
+        vec![],
+        bool::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_SET_ALLOWLIST_ENABLED,
+        vec![Parameter::new(ENABLED, bool::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_ALLOWLISTED,
+        vec![Parameter::new(STAKER, Key::cl_type())],
+        bool::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_ADD_TO_ALLOWLIST,
+        vec![Parameter::new(STAKERS, Vec::<Key>::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_REMOVE_FROM_ALLOWLIST,
+        vec![Parameter::new(STAKERS, Vec::<Key>::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_SET_MERKLE_ROOT,
+        vec![Parameter::new(MERKLE_ROOT, Option::<[u8; 32]>::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_EMERGENCY_WITHDRAW,
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_PAUSED,
+        vec![],
+        bool::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_PAUSE,
+        vec![],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_UNPAUSE,
+        vec![],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_ADD_REWARD,
+        vec![
+            Parameter::new(REWARD_AMOUNT, Key::cl_type()),
+            Parameter::new(WITHDRAWABLE_AMOUNT, Key::cl_type()),
+        ],
+        <()>::cl_type(),
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
@@ -421,5 +1444,102 @@ fn get_entry_points() -> EntryPoints {
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_ADD_ADMIN,
+        vec![Parameter::new(ADDRESS, Key::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_DISABLE_ADMIN,
+        vec![Parameter::new(ADDRESS, Key::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_SET_STAKING_TOTAL,
+        vec![Parameter::new(STAKING_TOTAL, U256::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_SET_STAKING_ENDS,
+        vec![Parameter::new(STAKING_ENDS, u64::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_SET_WITHDRAW_ENDS,
+        vec![Parameter::new(WITHDRAW_ENDS, u64::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_POOL_MODE,
+        vec![],
+        u8::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_REWARD_RATE,
+        vec![],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_SET_REWARD_RATE,
+        vec![Parameter::new(REWARD_RATE, U256::cl_type())],
+        <()>::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_CURRENT_EPOCH,
+        vec![],
+        u64::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_STAKER_EPOCH,
+        vec![Parameter::new(STAKER, Key::cl_type())],
+        u64::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_EPOCH_INFO,
+        vec![Parameter::new(EPOCH, u64::cl_type())],
+        EpochInfo::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_SCHEDULE_EPOCH,
+        vec![
+            Parameter::new(STAKING_STARTS, u64::cl_type()),
+            Parameter::new(STAKING_ENDS, u64::cl_type()),
+            Parameter::new(WITHDRAW_STARTS, u64::cl_type()),
+            Parameter::new(WITHDRAW_ENDS, u64::cl_type()),
+            Parameter::new(STAKING_TOTAL, U256::cl_type()),
+        ],
+        u64::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_ROLL_STAKE,
+        vec![Parameter::new(MERKLE_PROOF, Vec::<[u8; 32]>::cl_type())],
+        U256::cl_type(),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
     entry_points
 }
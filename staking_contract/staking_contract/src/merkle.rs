@@ -0,0 +1,33 @@
+//! Merkle proofs of membership in the allowlist of a staking campaign.
+//!
+//! A leaf is the blake2b hash of the serialized `Key` of a staker. Each level of the tree hashes
+//! a pair of nodes in ascending order, so a proof is only the list of sibling hashes from the
+//! leaf up to the root.
+use casper_types::{bytesrepr::ToBytes, Key};
+use contract_utils::{ContractStorage, UnwrapOrRevertIn};
+
+/// Returns the leaf of the given staker.
+pub fn leaf<S: ContractStorage>(storage: &S, staker: &Key) -> [u8; 32] {
+    storage.blake2b(&staker.to_bytes().unwrap_or_revert_in(storage))
+}
+
+/// Returns whether the proof leads from the leaf up to the root.
+pub fn verify<S: ContractStorage>(
+    storage: &S,
+    root: [u8; 32],
+    leaf: [u8; 32],
+    proof: &[[u8; 32]],
+) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (left, right) = if node <= *sibling {
+            (node, *sibling)
+        } else {
+            (*sibling, node)
+        };
+        let mut pair = [0u8; 64];
+        pair[..32].copy_from_slice(&left);
+        pair[32..].copy_from_slice(&right);
+        storage.blake2b(&pair)
+    });
+    computed == root
+}
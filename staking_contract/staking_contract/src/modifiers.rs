@@ -1,7 +1,8 @@
 // This code provides a set of functions for checking the validity of a transaction.
+use crate::data;
 use crate::error::Error;
-use casper_contract::contract_api::runtime;
-use casper_types::{BlockTime, U256};
+use casper_types::U256;
+use contract_utils::ContractStorage;
 
 /// Checks if the specified amount is positive.
 ///
@@ -20,6 +21,43 @@ pub fn positive(amount: U256) -> Result<(), Error> {
     }
 }
 
+/// Clips the specified amount to the room left under the staking total.
+///
+/// # Arguments
+///
+/// * `amount`: The amount requested.
+/// * `staking_total`, `staked_total`: The cap of the stakes, and what has been staked so far.
+///
+/// # Returns
+///
+/// A `Result`. If there is room left, the result will be `Ok` with the part of the amount that fits. Otherwise, the result will be `Err(Error::NotRequiredStake)`.
+pub fn within_capacity(
+    amount: U256,
+    staking_total: U256,
+    staked_total: U256,
+) -> Result<U256, Error> {
+    let remaining_capacity = staking_total.checked_sub(staked_total).unwrap_or_default();
+    let staked_amount = amount.min(remaining_capacity);
+    if staked_amount.is_zero() {
+        Err(Error::NotRequiredStake)
+    } else {
+        Ok(staked_amount)
+    }
+}
+
+/// Checks that the contract is not paused.
+///
+/// # Returns
+///
+/// A `Result`. If the contract is not paused, the result will be `Ok(())`. Otherwise, the result will be `Err(Error::ContractPaused)`.
+pub fn not_paused<S: ContractStorage>(storage: &S) -> Result<(), Error> {
+    if data::paused(storage) {
+        Err(Error::ContractPaused)
+    } else {
+        Ok(())
+    }
+}
+
 /// Checks if the current block time is after the specified event time.
 ///
 /// # Arguments
@@ -29,14 +67,31 @@ pub fn positive(amount: U256) -> Result<(), Error> {
 /// # Returns
 ///
 /// A `Result`. If the current block time is after the event time, the result will be `Ok(())`. If the current block time is before the event time, the result will be `Err(Error::BadTiming)`.
-pub fn after(event_time: u64) -> Result<(), Error> {
-    if runtime::get_blocktime() < BlockTime::new(event_time) {
+pub fn after<S: ContractStorage>(storage: &S, event_time: u64) -> Result<(), Error> {
+    if storage.block_time() < event_time {
         Err(Error::AfterBadTiming)
     } else {
         Ok(())
     }
 }
 
+/// Checks that a stake is no longer locked.
+///
+/// # Arguments
+///
+/// * `unlock_time`: The time until which the stake is locked.
+///
+/// # Returns
+///
+/// A `Result`. If the current block time is not before the unlock time, the result will be `Ok(())`. Otherwise, the result will be `Err(Error::StakeLocked)`.
+pub fn unlocked<S: ContractStorage>(storage: &S, unlock_time: u64) -> Result<(), Error> {
+    if storage.block_time() < unlock_time {
+        Err(Error::StakeLocked)
+    } else {
+        Ok(())
+    }
+}
+
 /// Checks if the current block time is before the specified event time.
 ///
 /// # Arguments
@@ -46,10 +101,41 @@ pub fn after(event_time: u64) -> Result<(), Error> {
 /// # Returns
 ///
 /// A `Result`. If the current block time is before the event time, the result will be `Ok(())`. If the current block time is after the event time, the result will be `Err(Error::BadTiming)`.
-pub fn before(event_time: u64) -> Result<(), Error> {
-    if runtime::get_blocktime() >= BlockTime::new(event_time) {
+pub fn before<S: ContractStorage>(storage: &S, event_time: u64) -> Result<(), Error> {
+    if storage.block_time() >= event_time {
         Err(Error::BeforeBadTiming)
     } else {
         Ok(())
     }
 }
+
+/// Checks that the staking and withdraw windows follow each other without a gap.
+///
+/// # Arguments
+///
+/// * `staking_starts`, `staking_ends`: The staking window.
+/// * `withdraw_starts`, `withdraw_ends`: The withdraw window.
+///
+/// # Returns
+///
+/// A `Result`. If the windows are ordered and `withdraw_starts` equals `staking_ends`, the result will be `Ok(())`. Otherwise the error names the broken constraint.
+pub fn ordered_windows(
+    staking_starts: u64,
+    staking_ends: u64,
+    withdraw_starts: u64,
+    withdraw_ends: u64,
+) -> Result<(), Error> {
+    if staking_ends < staking_starts {
+        return Err(Error::StakingEndsBeforeStakingStarts);
+    }
+    if withdraw_starts < staking_ends {
+        return Err(Error::WithdrawStartsStakingEnds);
+    }
+    if withdraw_ends < withdraw_starts {
+        return Err(Error::WithdrawEndsWithdrawStarts);
+    }
+    if staking_ends != withdraw_starts {
+        return Err(Error::GapBetweenStakingEndsWithdrawStarts);
+    }
+    Ok(())
+}
@@ -0,0 +1,269 @@
+//! Storage of the pools managed by the staking factory.
+//!
+//! Every pool field lives in the `pools` dictionary under a key namespaced by the pool id, e.g.
+//! `3_staking_total`, and stakes live in the `pool_stakes` dictionary under a hash of the staker
+//! and the pool id.
+use crate::data::{
+    ADDRESS, EARLY_WITHDRAW_REWARD, NAME, REWARD_BALANCE, STAKED_BALANCE, STAKED_TOTAL,
+    STAKING_ENDS, STAKING_STARTS, STAKING_TOTAL, TOTAL_REWARD, WITHDRAW_ENDS, WITHDRAW_STARTS,
+};
+use crate::error::Error;
+use alloc::{format, string::String};
+use casper_types::{
+    bytesrepr::{FromBytes, ToBytes},
+    CLTyped, ContractPackageHash, Key, U256,
+};
+use contract_utils::{
+    get_key, key_and_value_to_str, set_key, ContractStorage, Dict, UnwrapOrRevertIn,
+};
+use staking_math::{math, pool::FixedWindowPool};
+
+// Dictionary key for storing the fields of every pool
+const POOLS_DICT: &str = "pools";
+// Dictionary key for storing the amount staked by addresses in every pool
+const POOL_STAKES_DICT: &str = "pool_stakes";
+// Named key for storing the number of created pools
+const POOL_COUNT: &str = "pool_count";
+
+pub const ERC20_CONTRACT_PACKAGE_HASH: &str = "erc20_contract_package_hash";
+
+// Structure for managing a single pool
+pub struct Pool<'a, S: ContractStorage> {
+    storage: &'a S,
+    id: u64,
+    fields: Dict<'a, S>,
+    stakes: Dict<'a, S>,
+}
+
+impl<'a, S: ContractStorage> Pool<'a, S> {
+    /// Initializes the pool dictionaries and the pool counter
+    pub fn init(storage: &S) {
+        Dict::init(storage, POOLS_DICT);
+        Dict::init(storage, POOL_STAKES_DICT);
+        set_key(storage, POOL_COUNT, 0u64);
+    }
+
+    /// Returns the number of created pools
+    pub fn count(storage: &S) -> u64 {
+        get_key(storage, POOL_COUNT).unwrap_or_default()
+    }
+
+    /// Reserves the next pool id and returns the new pool
+    pub fn create(storage: &'a S) -> Pool<'a, S> {
+        let id = Self::count(storage);
+        set_key(storage, POOL_COUNT, id + 1);
+        Self::at(storage, id)
+    }
+
+    /// Returns the pool with the given id
+    pub fn instance(storage: &'a S, id: u64) -> Result<Pool<'a, S>, Error> {
+        if id >= Self::count(storage) {
+            return Err(Error::UnknownPool);
+        }
+        Ok(Self::at(storage, id))
+    }
+
+    fn at(storage: &'a S, id: u64) -> Pool<'a, S> {
+        Pool {
+            storage,
+            id,
+            fields: Dict::instance(storage, POOLS_DICT),
+            stakes: Dict::instance(storage, POOL_STAKES_DICT),
+        }
+    }
+
+    /// Returns the pool id
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn get<T: CLTyped + FromBytes>(&self, field: &str) -> Option<T> {
+        self.fields.get(&format!("{}_{}", self.id, field))
+    }
+
+    fn set<T: CLTyped + ToBytes>(&self, field: &str, value: T) {
+        self.fields.set(&format!("{}_{}", self.id, field), value);
+    }
+
+    /// Returns the state of the pool the reward math works on, in which stakes weigh their amount
+    pub fn state(&self) -> FixedWindowPool {
+        FixedWindowPool {
+            staking_ends: self.staking_ends(),
+            withdraw_ends: self.withdraw_ends(),
+            early_withdraw_reward: self.early_withdraw_reward(),
+            reward_balance: self.reward_balance(),
+            weighted_total: self.staked_total(),
+            weighted_balance: self.staked_balance(),
+        }
+    }
+
+    /// Retrieves the pool name
+    pub fn name(&self) -> String {
+        self.get(NAME).unwrap_or_revert_in(self.storage)
+    }
+
+    /// Sets the pool name
+    pub fn set_name(&self, name: String) {
+        self.set(NAME, name);
+    }
+
+    /// Retrieves the pool address
+    pub fn address(&self) -> String {
+        self.get(ADDRESS).unwrap_or_revert_in(self.storage)
+    }
+
+    /// Sets the pool address
+    pub fn set_address(&self, address: String) {
+        self.set(ADDRESS, address);
+    }
+
+    /// Retrieves the package hash of the ERC-20 token staked in the pool
+    pub fn erc20_contract_package_hash(&self) -> ContractPackageHash {
+        let key: Key = self
+            .get(ERC20_CONTRACT_PACKAGE_HASH)
+            .unwrap_or_revert_in(self.storage);
+        key.into_hash()
+            .map(ContractPackageHash::new)
+            .unwrap_or_revert_with_in(self.storage, Error::InvalidContractPackageHash)
+    }
+
+    /// Sets the package hash of the ERC-20 token staked in the pool
+    pub fn set_erc20_contract_package_hash(&self, erc20_contract_package_hash: Key) {
+        self.set(ERC20_CONTRACT_PACKAGE_HASH, erc20_contract_package_hash);
+    }
+
+    /// Retrieves the staking start time
+    pub fn staking_starts(&self) -> u64 {
+        self.get(STAKING_STARTS).unwrap_or_revert_in(self.storage)
+    }
+
+    /// Sets the staking start time
+    pub fn set_staking_starts(&self, staking_starts: u64) {
+        self.set(STAKING_STARTS, staking_starts);
+    }
+
+    /// Retrieves the staking end time
+    pub fn staking_ends(&self) -> u64 {
+        self.get(STAKING_ENDS).unwrap_or_revert_in(self.storage)
+    }
+
+    /// Sets the staking end time
+    pub fn set_staking_ends(&self, staking_ends: u64) {
+        self.set(STAKING_ENDS, staking_ends);
+    }
+
+    /// Retrieves the withdrawal start time
+    pub fn withdraw_starts(&self) -> u64 {
+        self.get(WITHDRAW_STARTS).unwrap_or_default()
+    }
+
+    /// Sets the withdrawal start time
+    pub fn set_withdraw_starts(&self, withdraw_starts: u64) {
+        self.set(WITHDRAW_STARTS, withdraw_starts);
+    }
+
+    /// Retrieves the withdrawal end time
+    pub fn withdraw_ends(&self) -> u64 {
+        self.get(WITHDRAW_ENDS).unwrap_or_default()
+    }
+
+    /// Sets the withdrawal end time
+    pub fn set_withdraw_ends(&self, withdraw_ends: u64) {
+        self.set(WITHDRAW_ENDS, withdraw_ends);
+    }
+
+    /// Retrieves the total staking amount
+    pub fn staking_total(&self) -> U256 {
+        self.get(STAKING_TOTAL).unwrap_or_default()
+    }
+
+    /// Sets the total staking amount
+    pub fn set_staking_total(&self, staking_total: U256) {
+        self.set(STAKING_TOTAL, staking_total);
+    }
+
+    /// Retrieves the total reward amount
+    pub fn total_reward(&self) -> U256 {
+        self.get(TOTAL_REWARD).unwrap_or_default()
+    }
+
+    /// Sets the total reward amount
+    pub fn set_total_reward(&self, total_reward: U256) {
+        self.set(TOTAL_REWARD, total_reward);
+    }
+
+    /// Retrieves the early withdrawal reward amount
+    pub fn early_withdraw_reward(&self) -> U256 {
+        self.get(EARLY_WITHDRAW_REWARD).unwrap_or_default()
+    }
+
+    /// Sets the early withdrawal reward amount
+    pub fn set_early_withdraw_reward(&self, early_withdraw_reward: U256) {
+        self.set(EARLY_WITHDRAW_REWARD, early_withdraw_reward);
+    }
+
+    /// Retrieves the total staked amount
+    pub fn staked_total(&self) -> U256 {
+        self.get(STAKED_TOTAL).unwrap_or_default()
+    }
+
+    /// Sets staked total
+    pub fn set_staked_total(&self, staked_total: U256) {
+        self.set(STAKED_TOTAL, staked_total);
+    }
+
+    /// Retrieves the reward balance
+    pub fn reward_balance(&self) -> U256 {
+        self.get(REWARD_BALANCE).unwrap_or_default()
+    }
+
+    /// Sets the reward balance
+    pub fn set_reward_balance(&self, reward_balance: U256) {
+        self.set(REWARD_BALANCE, reward_balance);
+    }
+
+    /// Retrieves the staked balance
+    pub fn staked_balance(&self) -> U256 {
+        self.get(STAKED_BALANCE).unwrap_or_default()
+    }
+
+    /// Sets the staked balance
+    pub fn set_staked_balance(&self, staked_balance: U256) {
+        self.set(STAKED_BALANCE, staked_balance);
+    }
+
+    /// Retrieves the amount staked in the pool by the given address
+    pub fn get_amount_staked_by_address(&self, address: &Key) -> Option<U256> {
+        self.stakes
+            .get(&key_and_value_to_str(self.storage, address, &self.id))
+    }
+
+    /// Adds a stake in the pool for the owner
+    pub fn add_stake(&self, owner: &Key, amount: &U256) -> Result<(), Error> {
+        let new_amount = if let Some(staked_amount) = self.get_amount_staked_by_address(owner) {
+            math::add(staked_amount, *amount)?
+        } else {
+            *amount
+        };
+        self.stakes.set(
+            &key_and_value_to_str(self.storage, owner, &self.id),
+            new_amount,
+        );
+        Ok(())
+    }
+
+    /// Withdraws a stake in the pool for the owner
+    pub fn withdraw_stake(&self, owner: &Key, amount: &U256) -> Result<(), Error> {
+        let staked_amount = self
+            .get_amount_staked_by_address(owner)
+            .ok_or(Error::NotAStaker)?;
+        let new_amount = staked_amount
+            .checked_sub(*amount)
+            .ok_or(Error::CheckedSub)?;
+        self.stakes.set(
+            &key_and_value_to_str(self.storage, owner, &self.id),
+            new_amount,
+        );
+        Ok(())
+    }
+}
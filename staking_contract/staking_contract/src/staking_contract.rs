@@ -1,20 +1,27 @@
+use crate::ces;
 use crate::detail;
+use crate::epochs::{self, Epoch};
 use crate::error::Error;
+use crate::info::{EpochInfo, PoolInfo, StakerInfo};
+use crate::merkle;
 use crate::modifiers;
 use crate::{
     address::Address,
-    data::{self, StakedTokens},
-    event::StakingContractEvent,
+    data::{
+        self, Allowances, Allowlist, ClaimedRewards, LockTiers, RewardDebts, StakePosition,
+        StakePositions, StakedTokens,
+    },
+    event::{self, StakingContractEvent},
 };
-use alloc::string::String;
-use casper_contract::{contract_api::runtime, unwrap_or_revert::UnwrapOrRevert};
-use casper_types::{runtime_args, BlockTime, ContractPackageHash, Key, RuntimeArgs, U256};
-use contract_utils::{ContractContext, ContractStorage};
+use alloc::{string::String, vec::Vec};
+use casper_types::{ContractPackageHash, Key, U256};
+use contract_utils::{ContractContext, ContractStorage, UnwrapOrRevertIn};
+use staking_math::pool::{EarlyWithdrawal, StreamingPool};
+use staking_math::{math, reward};
 
 const STACKING_CONTRACT_PACKAGE_HASH: &str = "stacking_contract_package_hash";
 const ERC20_CONTRACT_PACKAGE_HASH: &str = "erc20_contract_package_hash";
-
-const ENTRY_POINT_TRANSFER: &str = "transfer";
+const REWARD_TOKEN_PACKAGE_HASH: &str = "reward_token_package_hash";
 
 // This code defines a trait for the staking contract.
 #[allow(clippy::too_many_arguments)]
@@ -30,281 +37,1400 @@ pub trait CEP20STK<Storage: ContractStorage>: ContractContext<Storage> {
         withdraw_starts: u64,
         withdraw_ends: u64,
         staking_total: U256,
+        pool_mode: u8,
+        reward_rate: U256,
+        early_withdraw_penalty: u32,
+        penalty_treasury: Option<Address>,
+        symbol: String,
+        min_stake: U256,
+        max_stake_per_address: U256,
     ) -> Result<(), Error> {
-        if staking_ends < staking_starts {
-            return Err(Error::StakingEndsBeforeStakingStarts);
+        modifiers::ordered_windows(staking_starts, staking_ends, withdraw_starts, withdraw_ends)?;
+        if staking_starts < self.storage().block_time() {
+            return Err(Error::StakingStartsNow);
         }
-        if withdraw_starts < staking_ends {
-            return Err(Error::WithdrawStartsStakingEnds);
+        if pool_mode != data::POOL_MODE_FIXED_WINDOW && pool_mode != data::POOL_MODE_STREAMING {
+            return Err(Error::UnknownPoolMode);
         }
-        if withdraw_ends < withdraw_starts {
-            return Err(Error::WithdrawEndsWithdrawStarts);
+        if early_withdraw_penalty >= reward::MULTIPLIER_BASE {
+            return Err(Error::InvalidPenalty);
         }
-        if staking_starts < u64::from(runtime::get_blocktime()) {
-            return Err(Error::StakingStartsNow);
+        // Without a treasury the penalty goes back to the reward balance, so it must be paid in
+        // the same token.
+        if early_withdraw_penalty > 0
+            && penalty_treasury.is_none()
+            && self.reward_token_package_hash() != self.erc20_contract_package_hash()
+        {
+            return Err(Error::RewardTokenNotStakedToken);
+        }
+        data::set_name(self.storage(), name);
+        data::set_address(self.storage(), address);
+        // The windows, the cap and the reward accounting belong to the first epoch.
+        Epoch::init(self.storage());
+        data::set_staking_starts(self.storage(), staking_starts);
+        data::set_staking_ends(self.storage(), staking_ends);
+        data::set_withdraw_starts(self.storage(), withdraw_starts);
+        data::set_withdraw_ends(self.storage(), withdraw_ends);
+        data::set_staking_total(self.storage(), staking_total);
+        data::set_pool_mode(self.storage(), pool_mode);
+        data::set_reward_rate(self.storage(), reward_rate);
+        data::set_early_withdraw_penalty(self.storage(), early_withdraw_penalty);
+        data::set_penalty_treasury(self.storage(), penalty_treasury);
+        data::set_schema_version(self.storage(), data::CURRENT_SCHEMA_VERSION);
+        self.update_stake_limits(min_stake, max_stake_per_address)?;
+        // Receipts stand for staked tokens one for one, so they share their decimals.
+        data::set_symbol(self.storage(), symbol);
+        data::set_decimals(
+            self.storage(),
+            detail::decimals(self.storage(), self.erc20_contract_package_hash()),
+        );
+        // Streaming starts with the staking window.
+        data::set_last_reward_time(self.storage(), staking_starts);
+        // Initialize the staked tokens, allowances, allowlist, lock tiers, claimed rewards and
+        // reward debts maps.
+        StakedTokens::init(self.storage());
+        Allowances::init(self.storage());
+        Allowlist::init(self.storage());
+        StakePositions::init(self.storage());
+        LockTiers::init(self.storage());
+        ClaimedRewards::init(self.storage());
+        RewardDebts::init(self.storage());
+        // Register the events and create the dictionary they are appended to.
+        ces::init(self.storage(), event::schemas());
+        Ok(())
+    }
+
+    /// Carries the state written by a previous version of the contract forward to this one.
+    ///
+    /// Runs once per upgrade, after the new version has inherited the named keys of the previous
+    /// one. Each schema version adds its step below, guarded by the version it upgrades from, so
+    /// a contract can skip versions. Installs older than the upgrade support can't be upgraded,
+    /// so the first version has no step.
+    fn migrate(&mut self) -> Result<(), Error> {
+        let schema_version = data::schema_version(self.storage());
+        if schema_version > data::CURRENT_SCHEMA_VERSION {
+            return Err(Error::UnknownSchemaVersion);
         }
-        if staking_ends != withdraw_starts {
-            return Err(Error::GapBetweenStakingEndsWithdrawStarts);
-        }
-        data::set_name(name);
-        data::set_address(address);
-        data::set_staking_starts(staking_starts);
-        data::set_staking_ends(staking_ends);
-        data::set_withdraw_starts(withdraw_starts);
-        data::set_withdraw_ends(withdraw_ends);
-        data::set_staking_total(staking_total);
-        // Initialize the staked tokens map.
-        StakedTokens::init();
+        // the events of the new version replace those of the previous one
+        ces::migrate(self.storage(), event::schemas());
+        data::set_schema_version(self.storage(), data::CURRENT_SCHEMA_VERSION);
         Ok(())
     }
 
+    // Returns the version of the layout of the stored state.
+    fn schema_version(&self) -> u32 {
+        data::schema_version(self.storage())
+    }
+
     // Returns the contract name.
     fn name(&self) -> String {
-        data::name()
+        data::name(self.storage())
     }
 
     // Returns the contract address.
     fn address(&self) -> String {
-        data::address()
+        data::address(self.storage())
     }
 
     // Returns the staking start time.
     fn staking_starts(&self) -> u64 {
-        data::staking_starts()
+        data::staking_starts(self.storage())
     }
 
     // Returns the staking end time.
     fn staking_ends(&self) -> u64 {
-        data::staking_ends()
+        data::staking_ends(self.storage())
     }
 
     // Returns the withdraw start time.
     fn withdraw_starts(&self) -> u64 {
-        data::withdraw_starts()
+        data::withdraw_starts(self.storage())
     }
 
     // Returns the withdraw end time.
     fn withdraw_ends(&self) -> u64 {
-        data::withdraw_ends()
+        data::withdraw_ends(self.storage())
     }
 
     // Returns the total staking amount.
     fn staking_total(&self) -> U256 {
-        data::staking_total()
+        data::staking_total(self.storage())
     }
 
     // Sets the total staking amount.
     fn set_staking_total(&self, staking_total: U256) {
-        data::set_staking_total(staking_total)
+        data::set_staking_total(self.storage(), staking_total)
+    }
+
+    /// Updates the staking cap, which can't go below the amount that is already staked.
+    fn update_staking_total(&mut self, staking_total: U256) -> Result<(), Error> {
+        if staking_total < self.staked_total() {
+            return Err(Error::StakingTotalBelowStakedTotal);
+        }
+        self.set_staking_total(staking_total);
+        Ok(())
+    }
+
+    /// Returns the smallest amount a single stake can add.
+    fn min_stake(&self) -> U256 {
+        data::min_stake(self.storage())
+    }
+
+    /// Returns the largest amount an address can have staked, zero meaning no limit.
+    fn max_stake_per_address(&self) -> U256 {
+        data::max_stake_per_address(self.storage())
+    }
+
+    /// Updates the per address stake limits.
+    ///
+    /// Stakes that already break the new limits are left in place.
+    fn update_stake_limits(
+        &mut self,
+        min_stake: U256,
+        max_stake_per_address: U256,
+    ) -> Result<(), Error> {
+        if !max_stake_per_address.is_zero() && min_stake > max_stake_per_address {
+            return Err(Error::InvalidStakeLimits);
+        }
+        data::set_min_stake(self.storage(), min_stake);
+        data::set_max_stake_per_address(self.storage(), max_stake_per_address);
+        Ok(())
+    }
+
+    /// Checks that `amount` can be added to the stake of `staker`.
+    fn check_stake_limits(&self, staker: &Key, amount: U256) -> Result<(), Error> {
+        if amount < self.min_stake() {
+            return Err(Error::BelowMinStake);
+        }
+        self.check_max_stake_per_address(staker, amount)
+    }
+
+    /// Checks that the stake of `staker` stays within the per address limit once `amount` joins
+    /// it.
+    fn check_max_stake_per_address(&self, staker: &Key, amount: U256) -> Result<(), Error> {
+        let max_stake_per_address = self.max_stake_per_address();
+        let staked = StakedTokens::instance(self.storage())
+            .get_amount_staked_by_address(staker)
+            .unwrap_or_default();
+        if !max_stake_per_address.is_zero() && math::add(staked, amount)? > max_stake_per_address {
+            return Err(Error::AboveMaxStake);
+        }
+        Ok(())
+    }
+
+    /// Moves the end of the staking window while it is still open.
+    ///
+    /// The withdraw window starts exactly when staking ends, so `withdraw_starts` moves with it.
+    fn update_staking_ends(&mut self, staking_ends: u64) -> Result<(), Error> {
+        modifiers::before(self.storage(), self.staking_ends())?;
+        if staking_ends < self.storage().block_time() {
+            return Err(Error::WindowEndsInPast);
+        }
+        modifiers::ordered_windows(
+            self.staking_starts(),
+            staking_ends,
+            staking_ends,
+            self.withdraw_ends(),
+        )?;
+        data::set_staking_ends(self.storage(), staking_ends);
+        data::set_withdraw_starts(self.storage(), staking_ends);
+        Ok(())
+    }
+
+    /// Moves the end of the withdraw window while it is still open.
+    fn update_withdraw_ends(&mut self, withdraw_ends: u64) -> Result<(), Error> {
+        modifiers::before(self.storage(), self.withdraw_ends())?;
+        if withdraw_ends < self.storage().block_time() {
+            return Err(Error::WindowEndsInPast);
+        }
+        modifiers::ordered_windows(
+            self.staking_starts(),
+            self.staking_ends(),
+            self.withdraw_starts(),
+            withdraw_ends,
+        )?;
+        data::set_withdraw_ends(self.storage(), withdraw_ends);
+        Ok(())
+    }
+
+    // Returns the id of the current epoch.
+    fn current_epoch(&self) -> u64 {
+        Epoch::current_id(self.storage())
+    }
+
+    // Returns the id of the epoch the stake of the given address belongs to.
+    fn staker_epoch(&self, staker: Key) -> u64 {
+        epochs::staker_epoch(self.storage(), &staker)
+    }
+
+    /// Returns the windows, the cap and the reward accounting of the given epoch.
+    fn epoch_info(&self, epoch: u64) -> Result<EpochInfo, Error> {
+        let epoch = Epoch::instance(self.storage(), epoch)?;
+        Ok(EpochInfo {
+            staking_starts: epoch.staking_starts(),
+            staking_ends: epoch.staking_ends(),
+            withdraw_starts: epoch.withdraw_starts(),
+            withdraw_ends: epoch.withdraw_ends(),
+            staking_total: epoch.staking_total(),
+            total_reward: epoch.total_reward(),
+            early_withdraw_reward: epoch.early_withdraw_reward(),
+            staked_total: epoch.staked_total(),
+            reward_balance: epoch.reward_balance(),
+            staked_balance: epoch.staked_balance(),
+            weighted_total: epoch.weighted_total(),
+            weighted_balance: epoch.weighted_balance(),
+        })
+    }
+
+    /// Starts the next epoch, with its own windows and staking cap, once the current one has
+    /// closed, and returns its id.
+    ///
+    /// Stakes of the closed epoch stay there, earning its reward, until their stakers roll them
+    /// over or withdraw them. The reward of the new epoch is added with `add_reward`.
+    fn schedule_epoch(
+        &mut self,
+        staking_starts: u64,
+        staking_ends: u64,
+        withdraw_starts: u64,
+        withdraw_ends: u64,
+        staking_total: U256,
+    ) -> Result<u64, Error> {
+        if self.is_streaming() {
+            return Err(Error::NotFixedWindowPool);
+        }
+        let now = self.storage().block_time();
+        if now < self.withdraw_ends() {
+            return Err(Error::EpochNotClosed);
+        }
+        modifiers::ordered_windows(staking_starts, staking_ends, withdraw_starts, withdraw_ends)?;
+        if staking_starts < now {
+            return Err(Error::StakingStartsNow);
+        }
+        let epoch = Epoch::schedule(self.storage());
+        epoch.set_staking_starts(staking_starts);
+        epoch.set_staking_ends(staking_ends);
+        epoch.set_withdraw_starts(withdraw_starts);
+        epoch.set_withdraw_ends(withdraw_ends);
+        epoch.set_staking_total(staking_total);
+        Ok(epoch.id())
+    }
+
+    /// Fails when the stake of `staker` belongs to a past epoch, where it can only be rolled over
+    /// or withdrawn.
+    fn check_current_epoch(&self, staker: &Key) -> Result<(), Error> {
+        if !self.balance_of(*staker).is_zero()
+            && epochs::staker_epoch(self.storage(), staker) < self.current_epoch()
+        {
+            return Err(Error::StakeInPastEpoch);
+        }
+        Ok(())
+    }
+
+    /// Moves the caller's stake from the past epoch it belongs to into the current one, and
+    /// pays out the reward it earned there.
+    ///
+    /// The principal joins the current epoch with its weight and its lock, while the staking
+    /// window is open and within the staking cap. It is checked against the allowlist and the
+    /// stake limits like a new stake, and `merkle_proof` works as for `stake`.
+    fn roll_stake(&mut self, merkle_proof: Vec<[u8; 32]>) -> Result<U256, Error> {
+        modifiers::not_paused(self.storage())?;
+        modifiers::after(self.storage(), self.staking_starts())?;
+        modifiers::before(self.storage(), self.staking_ends())?;
+        let token_address = self.address();
+        let staker_address = detail::get_immediate_caller_address(self.storage())?;
+        let staker = Key::from(staker_address);
+        let amount = self.amount_staked(staker)?;
+        modifiers::positive(amount)?;
+        let epoch_id = self.staker_epoch(staker);
+        if epoch_id == self.current_epoch() {
+            return Err(Error::StakeInCurrentEpoch);
+        }
+        if math::add(self.staked_total(), amount)? > self.staking_total() {
+            return Err(Error::NotRequiredStake);
+        }
+        self.check_allowlist(&staker, &merkle_proof)?;
+        if amount < self.min_stake() {
+            return Err(Error::BelowMinStake);
+        }
+        // the rolled stake is already part of the stake of the address
+        self.check_max_stake_per_address(&staker, U256::zero())?;
+        let epoch = Epoch::instance(self.storage(), epoch_id)?;
+
+        // the past epoch has closed, so the stake gets its share of what is left of its reward
+        let weight = self.stake_weight(staker);
+        let claimed_rewards = ClaimedRewards::instance(self.storage());
+        let reward = self
+            .take_after_close_share(&epoch, weight)?
+            .saturating_sub(claimed_rewards.get_claimed_reward(&staker));
+        claimed_rewards.set_claimed_reward(&staker, U256::zero());
+        epochs::set_staker_epoch(self.storage(), &staker, self.current_epoch());
+        self.set_staked_total(math::add(self.staked_total(), amount)?);
+        self.set_staked_balance(math::add(self.staked_balance(), amount)?);
+        self.set_weighted_total(math::add(self.weighted_total(), weight)?);
+        self.set_weighted_balance(math::add(self.weighted_balance(), weight)?);
+        if !reward.is_zero() {
+            self.pay_direct(staker_address, U256::zero(), reward)?;
+        }
+
+        self.emit(StakingContractEvent::RolledOver {
+            token_address,
+            staker_address,
+            epoch: self.current_epoch(),
+            amount,
+            reward,
+        });
+        Ok(amount)
+    }
+
+    // Returns the pool mode.
+    fn pool_mode(&self) -> u8 {
+        data::pool_mode(self.storage())
+    }
+
+    /// Returns true when the reward streams every second instead of being shared over the
+    /// fixed windows.
+    fn is_streaming(&self) -> bool {
+        self.pool_mode() == data::POOL_MODE_STREAMING
+    }
+
+    // Returns the penalty, in basis points, taken out of the principal of early withdrawals.
+    fn early_withdraw_penalty(&self) -> u32 {
+        data::early_withdraw_penalty(self.storage())
+    }
+
+    // Returns the address early withdraw penalties are sent to, if they don't go back to the
+    // reward balance.
+    fn penalty_treasury(&self) -> Option<Address> {
+        data::penalty_treasury(self.storage())
+    }
+
+    // Returns the reward streamed per second.
+    fn reward_rate(&self) -> U256 {
+        data::reward_rate(self.storage())
+    }
+
+    /// Changes the reward streamed per second, from now on.
+    fn update_reward_rate(&mut self, reward_rate: U256) -> Result<(), Error> {
+        if !self.is_streaming() {
+            return Err(Error::NotStreamingPool);
+        }
+        self.update_pool()?;
+        data::set_reward_rate(self.storage(), reward_rate);
+        Ok(())
+    }
+
+    /// Returns the state of the pool in streaming mode the reward math works on.
+    ///
+    /// In streaming mode the reward balance only holds the reward that has not been streamed yet.
+    fn streaming_pool(&self) -> StreamingPool {
+        StreamingPool {
+            acc_reward_per_share: data::acc_reward_per_share(self.storage()),
+            last_reward_time: data::last_reward_time(self.storage()),
+            reward_rate: self.reward_rate(),
+            reward_balance: self.reward_balance(),
+            weighted_balance: self.weighted_balance(),
+        }
+    }
+
+    /// Brings the accumulated reward per staked token up to the current block time.
+    fn update_pool(&mut self) -> Result<(), Error> {
+        let now = self.storage().block_time();
+        if now <= data::last_reward_time(self.storage()) {
+            return Ok(());
+        }
+        let pool = self.streaming_pool().update(now)?;
+        data::set_acc_reward_per_share(self.storage(), pool.acc_reward_per_share);
+        self.set_reward_balance(pool.reward_balance);
+        data::set_last_reward_time(self.storage(), pool.last_reward_time);
+        Ok(())
     }
 
     // Returns the reward balance.
     fn reward_balance(&self) -> U256 {
-        data::reward_balance()
+        data::reward_balance(self.storage())
     }
 
     // Sets the staked balance.
     fn set_reward_balance(&self, reward_balance: U256) {
-        data::set_reward_balance(reward_balance)
+        data::set_reward_balance(self.storage(), reward_balance)
     }
 
     // Returns the staked balance.
     fn staked_balance(&self) -> U256 {
-        data::staked_balance()
+        data::staked_balance(self.storage())
     }
 
     // Sets the staked balance.
     fn set_staked_balance(&self, staked_balance: U256) {
-        data::set_staked_balance(staked_balance)
+        data::set_staked_balance(self.storage(), staked_balance)
     }
 
     // Returns the total reward.
     fn total_reward(&self) -> U256 {
-        data::total_reward()
+        data::total_reward(self.storage())
     }
 
     // Sets the total reward.
     fn set_total_reward(&self, total_reward: U256) {
-        data::set_total_reward(total_reward)
+        data::set_total_reward(self.storage(), total_reward)
     }
 
     // Returns the early withdraw reward.
     fn early_withdraw_reward(&self) -> U256 {
-        data::early_withdraw_reward()
+        data::early_withdraw_reward(self.storage())
     }
 
     // Sets the early withdraw reward.
     fn set_early_withdraw_reward(&self, early_withdraw_reward: U256) {
-        data::set_early_withdraw_reward(early_withdraw_reward)
+        data::set_early_withdraw_reward(self.storage(), early_withdraw_reward)
     }
 
     fn staked_total(&self) -> U256 {
-        data::staked_total()
+        data::staked_total(self.storage())
     }
 
     fn set_staked_total(&self, staked_total: U256) {
-        data::set_staked_total(staked_total)
+        data::set_staked_total(self.storage(), staked_total)
     }
 
     /// Returns the amount of tokens that have been staked by the given staker.
     fn amount_staked(&self, staker: Key) -> Result<U256, Error> {
-        StakedTokens::instance()
+        StakedTokens::instance(self.storage())
             .get_amount_staked_by_address(&staker)
             .ok_or(Error::NotAStaker)
     }
 
-    /// Stakes the given amount of tokens.
-    fn stake(&mut self, amount: U256) -> Result<U256, Error> {
-        modifiers::positive(amount)?;
-        modifiers::after(self.staking_starts())?;
-        modifiers::before(self.staking_ends())?;
-        // check for has enough tokens
+    // Returns the total weight of the stakes.
+    fn weighted_total(&self) -> U256 {
+        data::weighted_total(self.storage())
+    }
 
-        let token_address = self.address();
+    // Sets the total weight of the stakes.
+    fn set_weighted_total(&self, weighted_total: U256) {
+        data::set_weighted_total(self.storage(), weighted_total)
+    }
 
-        let stakers_dict = StakedTokens::instance();
-        let staker_address = detail::get_immediate_caller_address()
-            .unwrap_or_revert_with(Error::ImmediateCallerAddressFail);
+    // Returns the weight of the stakes that haven't been withdrawn.
+    fn weighted_balance(&self) -> U256 {
+        data::weighted_balance(self.storage())
+    }
 
-        let mut remaining_token = amount;
+    // Sets the weight of the stakes that haven't been withdrawn.
+    fn set_weighted_balance(&self, weighted_balance: U256) {
+        data::set_weighted_balance(self.storage(), weighted_balance)
+    }
 
-        if let Some(diff) = self.staking_total().checked_sub(amount) {
-            if remaining_token > diff {
-                remaining_token = diff;
-            }
+    // Returns the weight of the stake of the given staker.
+    fn stake_weight(&self, staker: Key) -> U256 {
+        StakedTokens::instance(self.storage()).get_weight(&staker)
+    }
+
+    // Returns the streamed reward the given staker has earned, and not been paid, as of the
+    // last update of the pool.
+    fn pending_streamed_reward(&self, staker: &Key) -> Result<U256, Error> {
+        Ok(reward::pending(
+            self.stake_weight(*staker),
+            data::acc_reward_per_share(self.storage()),
+            RewardDebts::instance(self.storage()).get_reward_debt(staker),
+        )?)
+    }
+
+    // Returns the time until which the stake of the given staker is locked.
+    fn unlock_time(&self, staker: Key) -> u64 {
+        StakedTokens::instance(self.storage()).get_unlock_time(&staker)
+    }
+
+    // Returns the reward multiplier, in basis points, of the given lock duration, or 0 if none.
+    fn lock_tier(&self, lock_duration: u64) -> u32 {
+        LockTiers::instance(self.storage())
+            .get_multiplier(lock_duration)
+            .unwrap_or_default()
+    }
+
+    /// Sets the reward multiplier, in basis points, of the given lock duration.
+    ///
+    /// A multiplier of zero closes the tier to new stakes. Stakes that aren't locked always weigh
+    /// their amount.
+    fn set_lock_tier(&mut self, lock_duration: u64, multiplier: u32) -> Result<(), Error> {
+        if lock_duration == 0 {
+            return Err(Error::UnknownLockTier);
         }
+        LockTiers::instance(self.storage()).set_multiplier(lock_duration, multiplier);
+        Ok(())
+    }
 
-        if remaining_token <= U256::from(0u64) {
-            return Err(Error::NotRequiredStake);
+    /// Returns the reward multiplier of a stake locked for `lock_duration`.
+    fn multiplier(&self, lock_duration: u64) -> Result<u32, Error> {
+        if lock_duration == 0 {
+            return Ok(reward::MULTIPLIER_BASE);
         }
+        match self.lock_tier(lock_duration) {
+            0 => Err(Error::UnknownLockTier),
+            multiplier => Ok(multiplier),
+        }
+    }
 
-        if (remaining_token + self.staked_total()) > self.staking_total() {
-            return Err(Error::NotRequiredStake);
+    /// Records `amount` staked by `staker` for `lock_duration` and returns the weight it adds.
+    ///
+    /// Staking again never shortens a lock: the whole stake unlocks at the latest unlock time.
+    fn add_weighted_stake(
+        &self,
+        staker: &Key,
+        amount: U256,
+        lock_duration: u64,
+    ) -> Result<U256, Error> {
+        let weight = reward::weight(amount, self.multiplier(lock_duration)?)?;
+        let stakers_dict = StakedTokens::instance(self.storage());
+        stakers_dict.add_stake(staker, &amount)?;
+        epochs::set_staker_epoch(self.storage(), staker, self.current_epoch());
+        StakePositions::instance(self.storage()).open(staker, amount);
+        stakers_dict.set_weight(staker, math::add(stakers_dict.get_weight(staker), weight)?);
+        let unlock_time = self
+            .storage()
+            .block_time()
+            .checked_add(lock_duration)
+            .ok_or(Error::Overflow)?;
+        if unlock_time > stakers_dict.get_unlock_time(staker) {
+            stakers_dict.set_unlock_time(staker, unlock_time);
+        }
+        self.set_staked_total(math::add(self.staked_total(), amount)?);
+        self.set_staked_balance(math::add(self.staked_balance(), amount)?);
+        self.set_weighted_total(math::add(self.weighted_total(), weight)?);
+        self.set_weighted_balance(math::add(self.weighted_balance(), weight)?);
+        Ok(weight)
+    }
+
+    /// Removes `amount` from the stake of `staker` and returns the weight that leaves with it.
+    ///
+    /// The totals are left to the caller, as they move differently depending on when the stake
+    /// leaves.
+    fn remove_weighted_stake(&self, staker: &Key, amount: U256) -> Result<U256, Error> {
+        let stakers_dict = StakedTokens::instance(self.storage());
+        let staked = stakers_dict
+            .get_amount_staked_by_address(staker)
+            .ok_or(Error::NotAStaker)?;
+        let stake_weight = stakers_dict.get_weight(staker);
+        let weight = reward::pro_rata(stake_weight, amount, staked)?;
+        stakers_dict.withdraw_stake(staker, &amount)?;
+        stakers_dict.set_weight(staker, math::sub(stake_weight, weight)?);
+        Ok(weight)
+    }
+
+    /// Returns whether only allowlisted addresses can stake.
+    fn allowlist_enabled(&self) -> bool {
+        data::allowlist_enabled(self.storage())
+    }
+
+    /// Turns the allowlist mode on or off.
+    fn set_allowlist_enabled(&mut self, enabled: bool) {
+        data::set_allowlist_enabled(self.storage(), enabled);
+    }
+
+    /// Returns whether the given address is on the allowlist, or has proven it is.
+    fn allowlisted(&self, staker: Key) -> bool {
+        Allowlist::instance(self.storage()).is_allowed(&staker)
+    }
+
+    /// Adds the given addresses to the allowlist.
+    fn add_to_allowlist(&mut self, stakers: Vec<Key>) {
+        let allowlist = Allowlist::instance(self.storage());
+        for staker in &stakers {
+            allowlist.set_allowed(staker, true);
         }
+    }
+
+    /// Removes the given addresses from the allowlist.
+    fn remove_from_allowlist(&mut self, stakers: Vec<Key>) {
+        let allowlist = Allowlist::instance(self.storage());
+        for staker in &stakers {
+            allowlist.set_allowed(staker, false);
+        }
+    }
+
+    /// Sets the root of the Merkle tree of allowlisted addresses, or none to only use the list.
+    fn set_merkle_root(&mut self, merkle_root: Option<[u8; 32]>) {
+        data::set_merkle_root(self.storage(), merkle_root);
+    }
 
-        self.pay_me(staker_address, remaining_token);
+    /// Checks that the address may hold a stake when the allowlist mode is on.
+    ///
+    /// An address missing from the list can prove its membership in the Merkle tree instead, and
+    /// is then added to the list so it doesn't have to prove it again.
+    fn check_allowlist(&mut self, staker: &Key, merkle_proof: &[[u8; 32]]) -> Result<(), Error> {
+        let allowlist = Allowlist::instance(self.storage());
+        if !self.allowlist_enabled() || allowlist.is_allowed(staker) {
+            return Ok(());
+        }
+        match data::merkle_root(self.storage()) {
+            Some(root)
+                if merkle::verify(
+                    self.storage(),
+                    root,
+                    merkle::leaf(self.storage(), staker),
+                    merkle_proof,
+                ) =>
+            {
+                allowlist.set_allowed(staker, true);
+                Ok(())
+            }
+            _ => Err(Error::NotAllowlisted),
+        }
+    }
+
+    /// Stakes the given amount of tokens, locked for `lock_duration` milliseconds.
+    ///
+    /// A lock duration of zero doesn't lock the stake; any other duration needs a lock tier.
+    fn stake(
+        &mut self,
+        amount: U256,
+        lock_duration: u64,
+        merkle_proof: Vec<[u8; 32]>,
+    ) -> Result<U256, Error> {
+        let staker_address = detail::get_immediate_caller_address(self.storage())?;
+        self.stake_for(staker_address, amount, lock_duration, merkle_proof)
+    }
+
+    /// Stakes the given amount of tokens of the caller, credited to the beneficiary.
+    ///
+    /// In allowlist mode the beneficiary must be on the list, or `merkle_proof` must prove it is
+    /// in the Merkle tree. Over the fixed windows, only the part that fits under the staking total
+    /// is staked and the rest is refunded to the caller. Only a staker can lock their own stake,
+    /// as a lock holds back the whole stake of its address. Returns the amount staked.
+    fn stake_for(
+        &mut self,
+        staker_address: Address,
+        amount: U256,
+        lock_duration: u64,
+        merkle_proof: Vec<[u8; 32]>,
+    ) -> Result<U256, Error> {
+        modifiers::not_paused(self.storage())?;
+        modifiers::positive(amount)?;
+        modifiers::after(self.storage(), self.staking_starts())?;
+        self.check_allowlist(&Key::from(staker_address), &merkle_proof)?;
+        self.check_current_epoch(&Key::from(staker_address))?;
+        self.multiplier(lock_duration)?;
+        let payer_address = detail::get_immediate_caller_address(self.storage())?;
+        if lock_duration != 0 && payer_address != staker_address {
+            return Err(Error::LockForAnotherStaker);
+        }
+        if self.is_streaming() {
+            return self.stake_streaming(payer_address, staker_address, amount, lock_duration);
+        }
+        modifiers::before(self.storage(), self.staking_ends())?;
+
+        let token_address = self.address();
+        // only what fits under the staking total is staked, the rest goes back to the payer
+        let staked_amount =
+            modifiers::within_capacity(amount, self.staking_total(), self.staked_total())?;
+        self.check_stake_limits(&Key::from(staker_address), staked_amount)?;
+
+        self.pay_me(payer_address, amount);
 
         self.emit(StakingContractEvent::Stake {
-            token_address,
+            token_address: token_address.clone(),
             staker_address,
+            payer_address,
             requested_amount: amount,
-            staked_amount: remaining_token,
+            staked_amount,
         });
 
-        if remaining_token < amount {
-            let refund = amount - remaining_token;
-            self.pay_to(staker_address, staker_address, refund);
+        if staked_amount < amount {
+            let refund = math::sub(amount, staked_amount)?;
+            detail::transfer(
+                self.storage(),
+                self.erc20_contract_package_hash(),
+                payer_address,
+                refund,
+            );
+            self.emit(StakingContractEvent::Refunded {
+                token_address,
+                staker_address: payer_address,
+                amount: refund,
+            });
+        }
+
+        self.add_weighted_stake(&Key::from(staker_address), staked_amount, lock_duration)?;
+        Ok(staked_amount)
+    }
+
+    /// Moves `amount` of the caller's stake to the recipient, without moving any tokens.
+    fn transfer_stake(&mut self, recipient_address: Address, amount: U256) -> Result<U256, Error> {
+        let staker_address = detail::get_immediate_caller_address(self.storage())?;
+        self.move_stake(staker_address, recipient_address, amount)
+    }
+
+    /// Moves `amount` of the stake of `staker_address` to the recipient.
+    ///
+    /// A locked stake can't move before its unlock time. The weight and the positions go with
+    /// the stake, and so does the part of the reward claimed over the fixed windows. When
+    /// streaming, the staker and the recipient are paid the reward they earned so far instead. In
+    /// allowlist mode the recipient must be on the list.
+    fn move_stake(
+        &mut self,
+        staker_address: Address,
+        recipient_address: Address,
+        amount: U256,
+    ) -> Result<U256, Error> {
+        modifiers::not_paused(self.storage())?;
+        modifiers::positive(amount)?;
+        let token_address = self.address();
+        let staker = Key::from(staker_address);
+        let recipient = Key::from(recipient_address);
+        // a lock holds back the whole stake of an address, so it isn't pushed onto another one
+        modifiers::unlocked(self.storage(), self.unlock_time(staker))?;
+        self.check_allowlist(&recipient, &[])?;
+        self.check_max_stake_per_address(&recipient, amount)?;
+        // the stake stays in its epoch, which the recipient's stake must belong to as well
+        let epoch = self.staker_epoch(staker);
+        if !self.balance_of(recipient).is_zero() && self.staker_epoch(recipient) != epoch {
+            return Err(Error::StakeInPastEpoch);
         }
+        // when streaming, both owners are paid what they earned on their weights so far
+        let mut streamed_rewards = Vec::new();
+        if self.is_streaming() {
+            self.update_pool()?;
+            streamed_rewards.push((staker_address, self.pending_streamed_reward(&staker)?));
+            if recipient != staker {
+                streamed_rewards
+                    .push((recipient_address, self.pending_streamed_reward(&recipient)?));
+            }
+        }
+
+        let claimed_rewards = ClaimedRewards::instance(self.storage());
+        let claimed = claimed_rewards.get_claimed_reward(&staker);
+        let claimed_part = reward::pro_rata(claimed, amount, self.amount_staked(staker)?)?;
+        StakePositions::instance(self.storage()).transfer_oldest(&staker, &recipient, amount)?;
+        let weight = self.remove_weighted_stake(&staker, amount)?;
 
-        self.set_staked_total(self.staked_total() + remaining_token);
-        self.set_staked_balance(self.staked_balance() + remaining_token);
-        stakers_dict.add_stake(&Key::from(staker_address), &remaining_token);
+        let stakers_dict = StakedTokens::instance(self.storage());
+        stakers_dict.add_stake(&recipient, &amount)?;
+        epochs::set_staker_epoch(self.storage(), &recipient, epoch);
+        stakers_dict.set_weight(
+            &recipient,
+            math::add(stakers_dict.get_weight(&recipient), weight)?,
+        );
+        claimed_rewards.set_claimed_reward(&staker, math::sub(claimed, claimed_part)?);
+        claimed_rewards.set_claimed_reward(
+            &recipient,
+            math::add(claimed_rewards.get_claimed_reward(&recipient), claimed_part)?,
+        );
+
+        self.emit(StakingContractEvent::StakeTransferred {
+            token_address: token_address.clone(),
+            staker_address,
+            recipient_address,
+            amount,
+        });
+        let acc_reward_per_share = data::acc_reward_per_share(self.storage());
+        for (address, reward) in streamed_rewards {
+            // from now on, each owner earns on their new weight
+            RewardDebts::instance(self.storage()).set_reward_debt(
+                &Key::from(address),
+                reward::reward_debt(self.stake_weight(Key::from(address)), acc_reward_per_share)?,
+            );
+            if !reward.is_zero() {
+                self.pay_direct(address, U256::zero(), reward)?;
+                self.emit(StakingContractEvent::PaidOut {
+                    token_address: token_address.clone(),
+                    staker_address: address,
+                    amount: U256::zero(),
+                    reward,
+                    penalty: U256::zero(),
+                });
+            }
+        }
         Ok(amount)
     }
 
-    /// Withdraws the given amount of tokens.
+    // Returns the symbol of the receipts.
+    fn symbol(&self) -> String {
+        data::symbol(self.storage())
+    }
+
+    // Returns the decimals of the receipts, which are the ones of the staked token.
+    fn decimals(&self) -> u8 {
+        data::decimals(self.storage())
+    }
+
+    // Returns the number of receipts in circulation, which is the amount staked.
+    fn total_supply(&self) -> U256 {
+        data::total_supply(self.storage())
+    }
+
+    /// Returns the receipts held by the given owner, which is the amount they have staked.
+    fn balance_of(&self, owner: Key) -> U256 {
+        StakedTokens::instance(self.storage())
+            .get_amount_staked_by_address(&owner)
+            .unwrap_or_default()
+    }
+
+    /// Returns the receipts the owner allows the spender to move.
+    fn allowance(&self, owner: Key, spender: Key) -> U256 {
+        Allowances::instance(self.storage()).get_allowance(&owner, &spender)
+    }
+
+    /// Allows the spender to move `amount` of the caller's receipts.
+    fn approve(&mut self, spender: Key, amount: U256) -> Result<(), Error> {
+        let owner = Key::from(detail::get_immediate_caller_address(self.storage())?);
+        Allowances::instance(self.storage()).set_allowance(&owner, &spender, amount);
+        Ok(())
+    }
+
+    /// Transfers `amount` of the caller's receipts, and the stake behind them, to the recipient.
+    fn transfer(&mut self, recipient_address: Address, amount: U256) -> Result<(), Error> {
+        self.transfer_stake(recipient_address, amount)?;
+        Ok(())
+    }
+
+    /// Transfers `amount` of the owner's receipts, and the stake behind them, to the recipient,
+    /// using the allowance the owner gave to the caller.
+    fn transfer_from(
+        &mut self,
+        owner_address: Address,
+        recipient_address: Address,
+        amount: U256,
+    ) -> Result<(), Error> {
+        let owner = Key::from(owner_address);
+        let spender = Key::from(detail::get_immediate_caller_address(self.storage())?);
+        let allowances = Allowances::instance(self.storage());
+        let allowance = allowances
+            .get_allowance(&owner, &spender)
+            .checked_sub(amount)
+            .ok_or(Error::InsufficientAllowance)?;
+        allowances.set_allowance(&owner, &spender, allowance);
+        self.move_stake(owner_address, recipient_address, amount)?;
+        Ok(())
+    }
+
+    // Returns the number of addresses that ever staked, including the ones that left.
+    fn staker_count(&self) -> u64 {
+        StakedTokens::instance(self.storage()).staker_count()
+    }
+
+    /// Returns up to `limit` stakers with their stake, starting with the staker at `offset`.
+    ///
+    /// Stakers are listed in the order they first staked, and keep their place with a stake of
+    /// zero once they leave.
+    fn stakers(&self, offset: u64, limit: u64) -> Vec<(Key, U256)> {
+        let stakers_dict = StakedTokens::instance(self.storage());
+        let end = offset
+            .saturating_add(limit)
+            .min(stakers_dict.staker_count());
+        (offset..end)
+            .map(|index| {
+                let staker = stakers_dict
+                    .get_staker(index)
+                    .unwrap_or_revert_in(self.storage());
+                let staked = stakers_dict
+                    .get_amount_staked_by_address(&staker)
+                    .unwrap_or_default();
+                (staker, staked)
+            })
+            .collect()
+    }
+
+    /// Returns the positions of the given staker, indexed by position id.
+    fn positions(&self, staker: Key) -> Vec<StakePosition> {
+        let positions = StakePositions::instance(self.storage());
+        (0..positions.count(&staker))
+            .map(|position_id| {
+                positions
+                    .get(&staker, position_id)
+                    .unwrap_or_revert_in(self.storage())
+            })
+            .collect()
+    }
+
+    /// Withdraws the given amount of tokens, taken from the oldest positions first.
     fn withdraw(&mut self, amount: U256) -> Result<U256, Error> {
-        modifiers::positive(amount)?;
-        modifiers::after(self.withdraw_starts())?;
+        let staker = Key::from(detail::get_immediate_caller_address(self.storage())?);
+        self.check_withdraw(staker, amount)?;
+        StakePositions::instance(self.storage()).withdraw_oldest(&staker, amount)?;
+        self.withdraw_amount(amount)
+    }
 
-        let stakers_dict = StakedTokens::instance();
-        let caller_address = detail::get_immediate_caller_address()?;
+    /// Withdraws the given amount of tokens from one position of the caller.
+    fn withdraw_position(&mut self, position_id: u64, amount: U256) -> Result<U256, Error> {
+        let staker = Key::from(detail::get_immediate_caller_address(self.storage())?);
+        self.check_withdraw(staker, amount)?;
+        StakePositions::instance(self.storage()).withdraw(&staker, position_id, amount)?;
+        self.withdraw_amount(amount)
+    }
 
-        if amount
-            > stakers_dict
-                .get_amount_staked_by_address(&Key::from(caller_address))
-                .unwrap()
-        {
+    /// Checks that the staker can withdraw the given amount of tokens now.
+    fn check_withdraw(&self, staker: Key, amount: U256) -> Result<(), Error> {
+        modifiers::not_paused(self.storage())?;
+        modifiers::positive(amount)?;
+        modifiers::unlocked(self.storage(), self.unlock_time(staker))?;
+        if !self.is_streaming() {
+            // a stake follows the windows of its own epoch, which have closed if it is a past one
+            let epoch = Epoch::instance(self.storage(), self.staker_epoch(staker))?;
+            modifiers::after(self.storage(), epoch.withdraw_starts())?;
+        }
+        if amount > self.amount_staked(staker)? {
             return Err(Error::NotRequiredStake);
         }
+        Ok(())
+    }
+
+    /// Withdraws the given amount of tokens, once it has been checked and the positions it comes
+    /// from are settled.
+    fn withdraw_amount(&mut self, amount: U256) -> Result<U256, Error> {
+        if self.is_streaming() {
+            return self.withdraw_streaming(amount);
+        }
+        let caller_address = detail::get_immediate_caller_address(self.storage())?;
+        let epoch = Epoch::instance(self.storage(), self.staker_epoch(Key::from(caller_address)))?;
 
         // different flows depending on when staking ends
-        if runtime::get_blocktime() < BlockTime::new(self.withdraw_ends()) {
+        if !epoch.state().is_closed(self.storage().block_time()) {
             self.withdraw_early(amount, caller_address)
         } else {
-            self.withdraw_after_close(amount, caller_address)
+            self.withdraw_after_close(amount, caller_address, epoch.id())
         }
     }
 
-    /// Withdraws the given amount of tokens early.
-    fn withdraw_early(&mut self, amount: U256, caller_address: Address) -> Result<U256, Error> {
-        let staker_address = detail::get_immediate_caller_address()
-            .unwrap_or_revert_with(Error::ImmediateCallerAddressFail);
+    /// Stakes the given amount of tokens of the payer in streaming mode, paying out the reward
+    /// the staker earned so far.
+    fn stake_streaming(
+        &mut self,
+        payer_address: Address,
+        staker_address: Address,
+        amount: U256,
+        lock_duration: u64,
+    ) -> Result<U256, Error> {
+        if math::add(self.staked_balance(), amount)? > self.staking_total() {
+            return Err(Error::NotRequiredStake);
+        }
         let token_address = self.address();
+        let staker = Key::from(staker_address);
+        self.check_stake_limits(&staker, amount)?;
 
-        let denom = U256::from(
-            self.withdraw_ends()
-                .checked_sub(self.staking_ends())
-                .ok_or(Error::CheckedSub)?,
-        ) * self.staked_total();
-
-        let reward: U256 = U256::from(
-            u64::from(runtime::get_blocktime())
-                .checked_sub(self.staking_ends())
-                .ok_or(Error::CheckedSub)?,
-        ) * self.early_withdraw_reward()
-            * amount
-            / denom;
-
-        let pay_out = amount + reward;
-
-        self.set_reward_balance(
-            self.reward_balance()
-                .checked_sub(reward)
-                .ok_or(Error::CheckedSub)?,
+        self.update_pool()?;
+        let acc_reward_per_share = data::acc_reward_per_share(self.storage());
+        let reward_debts = RewardDebts::instance(self.storage());
+        let stake_weight = self.stake_weight(staker);
+        let reward = reward::pending(
+            stake_weight,
+            acc_reward_per_share,
+            reward_debts.get_reward_debt(&staker),
+        )?;
+
+        self.pay_me(payer_address, amount);
+        let weight = self.add_weighted_stake(&staker, amount, lock_duration)?;
+        reward_debts.set_reward_debt(
+            &staker,
+            reward::reward_debt(math::add(stake_weight, weight)?, acc_reward_per_share)?,
         );
-        self.set_staked_balance(
-            self.staked_balance()
-                .checked_sub(amount)
-                .ok_or(Error::CheckedSub)?,
+
+        self.emit(StakingContractEvent::Stake {
+            token_address: token_address.clone(),
+            staker_address,
+            payer_address,
+            requested_amount: amount,
+            staked_amount: amount,
+        });
+        if !reward.is_zero() {
+            self.pay_direct(staker_address, U256::zero(), reward)?;
+            self.emit(StakingContractEvent::PaidOut {
+                token_address,
+                staker_address,
+                amount: U256::zero(),
+                reward,
+                penalty: U256::zero(),
+            });
+        }
+        Ok(amount)
+    }
+
+    /// Withdraws the given amount of tokens in streaming mode, together with the reward earned
+    /// so far.
+    fn withdraw_streaming(&mut self, amount: U256) -> Result<U256, Error> {
+        let token_address = self.address();
+        let staker_address = detail::get_immediate_caller_address(self.storage())?;
+        let staker = Key::from(staker_address);
+
+        self.update_pool()?;
+        let acc_reward_per_share = data::acc_reward_per_share(self.storage());
+        let reward_debts = RewardDebts::instance(self.storage());
+        let stake_weight = self.stake_weight(staker);
+        let reward = reward::pending(
+            stake_weight,
+            acc_reward_per_share,
+            reward_debts.get_reward_debt(&staker),
+        )?;
+
+        let weight = self.remove_weighted_stake(&staker, amount)?;
+        reward_debts.set_reward_debt(
+            &staker,
+            reward::reward_debt(math::sub(stake_weight, weight)?, acc_reward_per_share)?,
         );
-        let stakers_dict = StakedTokens::instance();
-        stakers_dict.withdraw_stake(&Key::from(caller_address), &amount)?;
+        // stakers can come and go, so the totals follow the balances
+        self.set_staked_total(math::sub(self.staked_total(), amount)?);
+        self.set_staked_balance(math::sub(self.staked_balance(), amount)?);
+        self.set_weighted_total(math::sub(self.weighted_total(), weight)?);
+        self.set_weighted_balance(math::sub(self.weighted_balance(), weight)?);
+
+        self.pay_direct(staker_address, amount, reward)?;
+        self.emit(StakingContractEvent::PaidOut {
+            token_address,
+            staker_address,
+            amount,
+            reward,
+            penalty: U256::zero(),
+        });
+        Ok(amount)
+    }
+
+    /// Withdraws the given amount of tokens early.
+    fn withdraw_early(&mut self, amount: U256, caller_address: Address) -> Result<U256, Error> {
+        let staker_address = detail::get_immediate_caller_address(self.storage())?;
+        let token_address = self.address();
+
+        // the part of the reward claimed beforehand is not paid again
+        let claimed_part = self.settle_claimed_reward(caller_address, amount)?;
+        let weight = self.remove_weighted_stake(&Key::from(caller_address), amount)?;
+        let EarlyWithdrawal {
+            accrued,
+            reward,
+            penalty,
+            paid_amount,
+        } = Epoch::current(self.storage()).state().early_withdrawal(
+            amount,
+            weight,
+            claimed_part,
+            self.early_withdraw_penalty(),
+            self.storage().block_time(),
+        )?;
+
+        self.set_reward_balance(math::sub(self.reward_balance(), accrued)?);
+        self.set_staked_balance(math::sub(self.staked_balance(), amount)?);
+        self.set_weighted_balance(math::sub(self.weighted_balance(), weight)?);
+        // the penalty goes to the treasury, or stays for the stakers who remain
+        if !penalty.is_zero() {
+            match self.penalty_treasury() {
+                Some(treasury) => detail::transfer(
+                    self.storage(),
+                    self.erc20_contract_package_hash(),
+                    treasury,
+                    penalty,
+                ),
+                None => self.set_reward_balance(math::add(self.reward_balance(), penalty)?),
+            }
+        }
         // pay the tokens
-        self.pay_direct(caller_address, pay_out)?;
+        self.pay_direct(caller_address, paid_amount, reward)?;
         // emit `PaidOut` event
         self.emit(StakingContractEvent::PaidOut {
             staker_address,
             token_address,
-            amount,
+            amount: paid_amount,
             reward,
+            penalty,
         });
         Ok(amount)
     }
 
-    /// Withdraws the given amount of tokens after the staking period has ended.
+    /// Withdraws the given amount of tokens after the given epoch has closed.
     fn withdraw_after_close(
         &mut self,
         amount: U256,
         caller_address: Address,
+        epoch: u64,
     ) -> Result<U256, Error> {
-        let staker_address = detail::get_immediate_caller_address()
-            .unwrap_or_revert_with(Error::ImmediateCallerAddressFail);
+        let staker_address = detail::get_immediate_caller_address(self.storage())?;
         let token_address = self.address();
 
-        let reward = self.reward_balance() * amount / self.staked_balance();
-        let pay_out = amount + reward;
-        let stakers_dict = StakedTokens::instance();
+        let claimed_part = self.settle_claimed_reward(caller_address, amount)?;
         // mutate stakers_dict accordingly to the situation
-        stakers_dict.withdraw_stake(&Key::from(caller_address), &amount)?;
-        self.pay_direct(caller_address, pay_out)?;
+        let weight = self.remove_weighted_stake(&Key::from(caller_address), amount)?;
+        let reward = self
+            .take_after_close_share(&Epoch::instance(self.storage(), epoch)?, weight)?
+            .saturating_sub(claimed_part);
+        self.pay_direct(caller_address, amount, reward)?;
         // emit `PaidOut` event
         self.emit(StakingContractEvent::PaidOut {
             staker_address,
             token_address,
             amount,
             reward,
+            penalty: U256::zero(),
         });
         Ok(amount)
     }
 
+    /// Takes the share of the reward of a stake of the given weight, leaving its closed epoch, out
+    /// of the balances of the epoch, and returns it.
+    ///
+    /// The share of every stake still in the epoch stays the same, or grows when a stake leaves
+    /// without its share.
+    fn take_after_close_share(&self, epoch: &Epoch<Storage>, weight: U256) -> Result<U256, Error> {
+        let share = epoch.state().after_close_reward(weight)?;
+        epoch.set_reward_balance(math::sub(epoch.reward_balance(), share)?);
+        epoch.set_weighted_balance(math::sub(epoch.weighted_balance(), weight)?);
+        Ok(share)
+    }
+
+    /// Releases the part of the reward claimed by the staker that goes with withdrawing `amount`,
+    /// and returns it.
+    fn settle_claimed_reward(&self, staker_address: Address, amount: U256) -> Result<U256, Error> {
+        let staker = Key::from(staker_address);
+        let claimed_rewards = ClaimedRewards::instance(self.storage());
+        let claimed = claimed_rewards.get_claimed_reward(&staker);
+        let claimed_part = reward::pro_rata_up(claimed, amount, self.amount_staked(staker)?)?;
+        claimed_rewards.set_claimed_reward(&staker, math::sub(claimed, claimed_part)?);
+        Ok(claimed_part)
+    }
+
+    /// Returns the reward that a stake of the given weight has earned so far over the fixed
+    /// windows of the given epoch, whether it was claimed or not.
+    fn accrued_reward(&self, epoch: &Epoch<Storage>, weight: U256) -> Result<U256, Error> {
+        Ok(epoch
+            .state()
+            .accrued_reward(weight, self.storage().block_time())?)
+    }
+
+    /// Pays out the reward the caller has earned so far, keeping the stake in place.
+    fn claim_rewards(&mut self) -> Result<U256, Error> {
+        modifiers::not_paused(self.storage())?;
+        let token_address = self.address();
+        let staker_address = detail::get_immediate_caller_address(self.storage())?;
+        let staker = Key::from(staker_address);
+        self.amount_staked(staker)?;
+        let stake_weight = self.stake_weight(staker);
+
+        let reward = if self.is_streaming() {
+            self.update_pool()?;
+            let acc_reward_per_share = data::acc_reward_per_share(self.storage());
+            let reward_debts = RewardDebts::instance(self.storage());
+            let reward = reward::pending(
+                stake_weight,
+                acc_reward_per_share,
+                reward_debts.get_reward_debt(&staker),
+            )?;
+            reward_debts.set_reward_debt(
+                &staker,
+                reward::reward_debt(stake_weight, acc_reward_per_share)?,
+            );
+            reward
+        } else {
+            let claimed_rewards = ClaimedRewards::instance(self.storage());
+            let claimed = claimed_rewards.get_claimed_reward(&staker);
+            let epoch = Epoch::instance(self.storage(), self.staker_epoch(staker))?;
+            let reward = self
+                .accrued_reward(&epoch, stake_weight)?
+                .saturating_sub(claimed);
+            claimed_rewards.set_claimed_reward(&staker, math::add(claimed, reward)?);
+            reward
+        };
+        if reward.is_zero() {
+            return Err(Error::NothingToClaim);
+        }
+
+        self.pay_direct(staker_address, U256::zero(), reward)?;
+        self.emit(StakingContractEvent::PaidOut {
+            token_address,
+            staker_address,
+            amount: U256::zero(),
+            reward,
+            penalty: U256::zero(),
+        });
+        Ok(reward)
+    }
+
+    /// Returns true while stakes, rewards and reward payouts are stopped.
+    fn paused(&self) -> bool {
+        data::paused(self.storage())
+    }
+
+    /// Stops stakes, rewards and reward payouts until the contract is unpaused.
+    fn pause(&mut self) -> Result<(), Error> {
+        modifiers::not_paused(self.storage())?;
+        data::set_paused(self.storage(), true);
+        self.emit(StakingContractEvent::Paused {
+            account: detail::get_immediate_caller_address(self.storage())?,
+        });
+        Ok(())
+    }
+
+    /// Resumes stakes, rewards and reward payouts.
+    fn unpause(&mut self) -> Result<(), Error> {
+        if !self.paused() {
+            return Err(Error::ContractNotPaused);
+        }
+        data::set_paused(self.storage(), false);
+        self.emit(StakingContractEvent::Unpaused {
+            account: detail::get_immediate_caller_address(self.storage())?,
+        });
+        Ok(())
+    }
+
+    /// Returns the whole stake of the caller and gives up any reward, even while paused.
+    ///
+    /// A locked stake can leave too, as it gives up the reward its lock was weighted for. The
+    /// given up reward stays with the other stakers: when streaming, what the stake has earned
+    /// goes back to the reward balance, and over the fixed windows the weight of the stake leaves
+    /// the balances of its epoch while the reward it hasn't claimed stays, which raises the share
+    /// of the stakes still in the epoch once it closes.
+    fn emergency_withdraw(&mut self) -> Result<U256, Error> {
+        let token_address = self.address();
+        let staker_address = detail::get_immediate_caller_address(self.storage())?;
+        let staker = Key::from(staker_address);
+        let amount = self.amount_staked(staker)?;
+        modifiers::positive(amount)?;
+        if self.is_streaming() {
+            self.update_pool()?;
+        }
+
+        StakePositions::instance(self.storage()).withdraw_oldest(&staker, amount)?;
+        let weight = self.remove_weighted_stake(&staker, amount)?;
+        StakedTokens::instance(self.storage()).set_unlock_time(&staker, 0);
+        if self.is_streaming() {
+            // what the stake has earned streams again, to the stakers who remain
+            let reward_debts = RewardDebts::instance(self.storage());
+            let given_up = reward::pending(
+                weight,
+                data::acc_reward_per_share(self.storage()),
+                reward_debts.get_reward_debt(&staker),
+            )?;
+            reward_debts.set_reward_debt(&staker, U256::zero());
+            self.set_reward_balance(math::add(self.reward_balance(), given_up)?);
+            self.set_staked_total(math::sub(self.staked_total(), amount)?);
+            self.set_staked_balance(math::sub(self.staked_balance(), amount)?);
+            self.set_weighted_total(math::sub(self.weighted_total(), weight)?);
+            self.set_weighted_balance(math::sub(self.weighted_balance(), weight)?);
+        } else {
+            let now = self.storage().block_time();
+            let claimed_rewards = ClaimedRewards::instance(self.storage());
+            let epoch = Epoch::instance(self.storage(), self.staker_epoch(staker))?;
+            if epoch.is_current() && now < epoch.staking_ends() {
+                // nothing has been earned yet, so the stake is undone
+                self.set_staked_total(math::sub(self.staked_total(), amount)?);
+                self.set_staked_balance(math::sub(self.staked_balance(), amount)?);
+                self.set_weighted_total(math::sub(self.weighted_total(), weight)?);
+                self.set_weighted_balance(math::sub(self.weighted_balance(), weight)?);
+            } else {
+                // the reward claimed so far has already left the reward balance, and the rest
+                // stays for the stakes still in the epoch
+                epoch.set_reward_balance(
+                    epoch
+                        .reward_balance()
+                        .saturating_sub(claimed_rewards.get_claimed_reward(&staker)),
+                );
+                epoch.set_weighted_balance(math::sub(epoch.weighted_balance(), weight)?);
+                if !epoch.state().is_closed(now) {
+                    epoch.set_staked_balance(math::sub(epoch.staked_balance(), amount)?);
+                }
+            }
+            claimed_rewards.set_claimed_reward(&staker, U256::zero());
+        }
+
+        detail::transfer(
+            self.storage(),
+            self.erc20_contract_package_hash(),
+            staker_address,
+            amount,
+        );
+        self.emit(StakingContractEvent::EmergencyWithdraw {
+            token_address,
+            staker_address,
+            amount,
+        });
+        Ok(amount)
+    }
+
+    /// Adds the reward the caller has earned so far to their stake, without transferring it.
+    ///
+    /// Only available when rewards are paid in the staked token, and still bound by the staking
+    /// total. Over the fixed windows the compounded reward counts as claimed, so it is not paid a
+    /// second time on withdraw, and it adds no weight: the reward is only earned once staking has
+    /// closed, and is shared by the weights staked by then.
+    fn compound(&mut self) -> Result<U256, Error> {
+        modifiers::not_paused(self.storage())?;
+        if self.reward_token_package_hash() != self.erc20_contract_package_hash() {
+            return Err(Error::RewardTokenNotStakedToken);
+        }
+        let token_address = self.address();
+        let staker_address = detail::get_immediate_caller_address(self.storage())?;
+        let staker = Key::from(staker_address);
+        let staked = self.amount_staked(staker)?;
+        self.check_current_epoch(&staker)?;
+        let stake_weight = self.stake_weight(staker);
+
+        let reward = if self.is_streaming() {
+            self.update_pool()?;
+            let reward = reward::pending(
+                stake_weight,
+                data::acc_reward_per_share(self.storage()),
+                RewardDebts::instance(self.storage()).get_reward_debt(&staker),
+            )?;
+            if math::add(self.staked_balance(), reward)? > self.staking_total() {
+                return Err(Error::NotRequiredStake);
+            }
+            reward
+        } else {
+            let claimed_rewards = ClaimedRewards::instance(self.storage());
+            let claimed = claimed_rewards.get_claimed_reward(&staker);
+            let epoch = Epoch::instance(self.storage(), self.staker_epoch(staker))?;
+            let reward = self
+                .accrued_reward(&epoch, stake_weight)?
+                .saturating_sub(claimed);
+            if math::add(self.staked_total(), reward)? > self.staking_total() {
+                return Err(Error::NotRequiredStake);
+            }
+            claimed_rewards.set_claimed_reward(&staker, math::add(claimed, reward)?);
+            reward
+        };
+        if reward.is_zero() {
+            return Err(Error::NothingToClaim);
+        }
+
+        // when streaming, the reward joins the stake at the multiplier the stake already has
+        let weight = if self.is_streaming() {
+            reward::pro_rata(stake_weight, reward, staked)?
+        } else {
+            U256::zero()
+        };
+        let stakers_dict = StakedTokens::instance(self.storage());
+        stakers_dict.add_stake(&staker, &reward)?;
+        stakers_dict.set_weight(&staker, math::add(stake_weight, weight)?);
+        StakePositions::instance(self.storage()).open(&staker, reward);
+        self.set_staked_total(math::add(self.staked_total(), reward)?);
+        self.set_staked_balance(math::add(self.staked_balance(), reward)?);
+        self.set_weighted_total(math::add(self.weighted_total(), weight)?);
+        self.set_weighted_balance(math::add(self.weighted_balance(), weight)?);
+        if self.is_streaming() {
+            RewardDebts::instance(self.storage()).set_reward_debt(
+                &staker,
+                reward::reward_debt(
+                    math::add(stake_weight, weight)?,
+                    data::acc_reward_per_share(self.storage()),
+                )?,
+            );
+        }
+
+        self.emit(StakingContractEvent::Compounded {
+            token_address,
+            staker_address,
+            reward,
+            staked_amount: math::add(staked, reward)?,
+        });
+        Ok(reward)
+    }
+
     /// Adds the given amount of reward tokens.
     fn add_reward(
         &mut self,
         reward_amount: U256,
         withdrawable_amount: U256,
     ) -> Result<U256, Error> {
-        modifiers::before(self.withdraw_starts())?;
+        modifiers::not_paused(self.storage())?;
+        // in streaming mode rewards can be topped up at any time
+        if self.is_streaming() {
+            self.update_pool()?;
+        } else {
+            modifiers::before(self.storage(), self.withdraw_starts())?;
+        }
 
         // reward_amount has to be positive
         if reward_amount <= U256::from(0u64) {
@@ -318,102 +1444,182 @@ pub trait CEP20STK<Storage: ContractStorage>: ContractContext<Storage> {
         if withdrawable_amount > reward_amount {
             return Err(Error::NegativeWithdrawableReward);
         }
-        self.pay_me(detail::get_immediate_caller_address()?, reward_amount);
+        let account = detail::get_immediate_caller_address(self.storage())?;
+        self.pay_me_reward(account, reward_amount);
 
         // calculate new total reward
-        let current_total_reward = self.total_reward() + reward_amount;
+        let current_total_reward = math::add(self.total_reward(), reward_amount)?;
 
         self.set_total_reward(current_total_reward);
-        self.set_reward_balance(current_total_reward);
-        self.set_early_withdraw_reward(self.early_withdraw_reward() + withdrawable_amount);
+        self.set_reward_balance(math::add(self.reward_balance(), reward_amount)?);
+        self.set_early_withdraw_reward(math::add(
+            self.early_withdraw_reward(),
+            withdrawable_amount,
+        )?);
 
+        self.emit(StakingContractEvent::AddReward {
+            token_address: self.address(),
+            account,
+            reward_amount,
+            withdrawable_amount,
+        });
         Ok(reward_amount)
     }
 
-    /// Returns the reward that the given staker is entitled to.
+    /// Returns the reward that the given staker is entitled to and hasn't claimed yet.
     fn staker_reward(&mut self, staker_address: Key) -> Result<U256, Error> {
-        let amount = self.amount_staked(staker_address)?;
-        let reward: U256 = if runtime::get_blocktime() < BlockTime::new(self.staking_ends()) {
-            let denom = U256::from(
-                self.withdraw_ends()
-                    .checked_sub(self.staking_ends())
-                    .ok_or(Error::CheckedSub)?,
-            ) * self.staked_total();
-
-            U256::from(
-                u64::from(runtime::get_blocktime())
-                    .checked_sub(self.staking_ends())
-                    .ok_or(Error::CheckedSub)?,
-            ) * amount
-                / denom
-        } else {
-            self.reward_balance() * amount / self.staked_balance()
-        };
-
-        Ok(reward)
+        self.amount_staked(staker_address)?;
+        let stake_weight = self.stake_weight(staker_address);
+        if self.is_streaming() {
+            return Ok(self.streaming_pool().pending_reward(
+                stake_weight,
+                RewardDebts::instance(self.storage()).get_reward_debt(&staker_address),
+                self.storage().block_time(),
+            )?);
+        }
+        let claimed = ClaimedRewards::instance(self.storage()).get_claimed_reward(&staker_address);
+        let epoch = Epoch::instance(self.storage(), self.staker_epoch(staker_address))?;
+        Ok(self
+            .accrued_reward(&epoch, stake_weight)?
+            .saturating_sub(claimed))
     }
 
-    /// Pays the given amount of tokens directly to the recipient.
-    fn pay_direct(&self, recipient: Address, amount: U256) -> Result<(), Error> {
-        modifiers::positive(amount)?;
-        let erc20_contract_package_hash = self.erc20_contract_package_hash();
+    /// Returns the whole state of the pool.
+    fn pool_info(&self) -> PoolInfo {
+        PoolInfo {
+            name: self.name(),
+            address: self.address(),
+            erc20_contract_package_hash: self.erc20_contract_package_hash(),
+            reward_token_package_hash: self.reward_token_package_hash(),
+            staking_starts: self.staking_starts(),
+            staking_ends: self.staking_ends(),
+            withdraw_starts: self.withdraw_starts(),
+            withdraw_ends: self.withdraw_ends(),
+            staking_total: self.staking_total(),
+            total_reward: self.total_reward(),
+            early_withdraw_reward: self.early_withdraw_reward(),
+            staked_total: self.staked_total(),
+            reward_balance: self.reward_balance(),
+            staked_balance: self.staked_balance(),
+            weighted_total: self.weighted_total(),
+            weighted_balance: self.weighted_balance(),
+            pool_mode: self.pool_mode(),
+            reward_rate: self.reward_rate(),
+            acc_reward_per_share: data::acc_reward_per_share(self.storage()),
+            last_reward_time: data::last_reward_time(self.storage()),
+            paused: self.paused(),
+            early_withdraw_penalty: self.early_withdraw_penalty(),
+            penalty_treasury: self.penalty_treasury(),
+            symbol: self.symbol(),
+            decimals: self.decimals(),
+            total_supply: self.total_supply(),
+            min_stake: self.min_stake(),
+            max_stake_per_address: self.max_stake_per_address(),
+            allowlist_enabled: self.allowlist_enabled(),
+            merkle_root: data::merkle_root(self.storage()),
+            staker_count: self.staker_count(),
+            current_epoch: self.current_epoch(),
+        }
+    }
 
-        let args = runtime_args! {
-            "recipient" => recipient,
-            "amount" => amount,
+    /// Returns the state of the stake of the given address, which is empty for non stakers.
+    fn staker_info(&mut self, staker: Key) -> Result<StakerInfo, Error> {
+        let reward = match self.staker_reward(staker) {
+            Err(Error::NotAStaker) => U256::zero(),
+            reward => reward?,
         };
-        runtime::call_versioned_contract::<()>(
-            erc20_contract_package_hash,
-            None,
-            ENTRY_POINT_TRANSFER,
-            args,
-        );
+        Ok(StakerInfo {
+            amount_staked: self.balance_of(staker),
+            reward,
+            weight: self.stake_weight(staker),
+            unlock_time: self.unlock_time(staker),
+            claimed_reward: ClaimedRewards::instance(self.storage()).get_claimed_reward(&staker),
+            positions: self.positions(staker).into_iter().map(Into::into).collect(),
+            epoch: self.staker_epoch(staker),
+        })
+    }
+
+    /// Pays the given amount of staked tokens and reward tokens directly to the recipient.
+    ///
+    /// Principal and reward are sent as two separate transfers, so they can use different tokens.
+    fn pay_direct(&self, recipient: Address, amount: U256, reward: U256) -> Result<(), Error> {
+        modifiers::positive(math::add(amount, reward)?)?;
+        if !amount.is_zero() {
+            detail::transfer(
+                self.storage(),
+                self.erc20_contract_package_hash(),
+                recipient,
+                amount,
+            );
+        }
+        if !reward.is_zero() {
+            detail::transfer(
+                self.storage(),
+                self.reward_token_package_hash(),
+                recipient,
+                reward,
+            );
+        }
         Ok(())
     }
 
     /// Pays the given amount of tokens to the recipient, transferring them from the given allower.
     fn pay_to(&self, allower: Address, recipient: Address, amount: U256) {
-        let erc20_contract_package_hash = self.erc20_contract_package_hash();
-        let args = runtime_args! {
-            "owner" => allower,
-            "recipient" => recipient,
-            "amount" => amount
-        };
-        runtime::call_versioned_contract::<()>(
-            erc20_contract_package_hash,
-            None,
-            "transfer_from",
-            args,
+        detail::transfer_from(
+            self.storage(),
+            self.erc20_contract_package_hash(),
+            allower,
+            recipient,
+            amount,
         );
     }
 
     /// Pays the given amount of tokens to the staking contract, transferring them from the given allower.
     fn pay_me(&self, payer: Address, amount: U256) {
-        #[allow(clippy::redundant_closure)]
-        let stacking_contract_package_hash = runtime::get_key(STACKING_CONTRACT_PACKAGE_HASH)
-            .unwrap_or_revert_with(Error::MissingContractPackageHash)
-            .into_hash()
-            .map(|hash_address| ContractPackageHash::new(hash_address))
-            .unwrap_or_revert_with(Error::InvalidContractPackageHash);
-        self.pay_to(
+        self.pay_to(payer, self.contract_address(), amount)
+    }
+
+    /// Pays the given amount of reward tokens to the staking contract, transferring them from the given allower.
+    fn pay_me_reward(&self, payer: Address, amount: U256) {
+        detail::transfer_from(
+            self.storage(),
+            self.reward_token_package_hash(),
             payer,
-            crate::address::Address::ContractPackage(stacking_contract_package_hash),
+            self.contract_address(),
             amount,
-        )
+        );
+    }
+
+    /// Returns the address under which the staking contract holds tokens.
+    fn contract_address(&self) -> Address {
+        Address::ContractPackage(detail::get_contract_package_hash(
+            self.storage(),
+            STACKING_CONTRACT_PACKAGE_HASH,
+        ))
     }
 
     /// Emits the events
     fn emit(&mut self, event: StakingContractEvent) {
-        data::emit(&event);
+        data::emit(self.storage(), &event);
     }
 
     /// Returns `ContractPackageHash` of the ERC-20 type token that is staked
     fn erc20_contract_package_hash(&self) -> ContractPackageHash {
-        #[allow(clippy::redundant_closure)]
-        runtime::get_key(ERC20_CONTRACT_PACKAGE_HASH)
-            .unwrap_or_revert_with(Error::MissingContractPackageHash)
-            .into_hash()
-            .map(|hash_address| ContractPackageHash::new(hash_address))
-            .unwrap_or_revert_with(Error::InvalidContractHash)
+        detail::get_contract_package_hash(self.storage(), ERC20_CONTRACT_PACKAGE_HASH)
+    }
+
+    /// Returns `ContractPackageHash` of the ERC-20 type token that rewards are paid in.
+    ///
+    /// Falls back to the staked token when no reward token was set at install time.
+    fn reward_token_package_hash(&self) -> ContractPackageHash {
+        if self
+            .storage()
+            .get_named_key(REWARD_TOKEN_PACKAGE_HASH)
+            .is_some()
+        {
+            detail::get_contract_package_hash(self.storage(), REWARD_TOKEN_PACKAGE_HASH)
+        } else {
+            self.erc20_contract_package_hash()
+        }
     }
 }
@@ -0,0 +1,267 @@
+use crate::ces;
+use crate::detail;
+use crate::error::Error;
+use crate::modifiers;
+use crate::pools::Pool;
+use crate::{
+    address::Address,
+    data::{
+        self, ADDRESS, EARLY_WITHDRAW_REWARD, NAME, REWARD_BALANCE, STAKED_BALANCE, STAKED_TOTAL,
+        STAKING_ENDS, STAKING_STARTS, STAKING_TOTAL, TOTAL_REWARD, WITHDRAW_ENDS, WITHDRAW_STARTS,
+    },
+    event::{self, StakingContractEvent},
+    pools::ERC20_CONTRACT_PACKAGE_HASH,
+};
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+};
+use casper_types::{Key, U256};
+use contract_utils::{ContractContext, ContractStorage};
+use staking_math::math;
+
+const STACKING_CONTRACT_PACKAGE_HASH: &str = "stacking_contract_package_hash";
+
+// This code defines a trait for the staking factory, which runs many pools with the rules of
+// `CEP20STK` in a single contract.
+pub trait StakingFactory<Storage: ContractStorage>: ContractContext<Storage> {
+    // Initializes the staking factory.
+    fn init(&mut self) {
+        Pool::init(self.storage());
+        ces::init(self.storage(), event::schemas());
+    }
+
+    /// Creates a new pool and returns its id.
+    #[allow(clippy::too_many_arguments)]
+    fn create_pool(
+        &mut self,
+        name: String,
+        address: String,
+        erc20_contract_package_hash: Key,
+        staking_starts: u64,
+        staking_ends: u64,
+        withdraw_starts: u64,
+        withdraw_ends: u64,
+        staking_total: U256,
+    ) -> Result<u64, Error> {
+        modifiers::ordered_windows(staking_starts, staking_ends, withdraw_starts, withdraw_ends)?;
+        if staking_starts < self.storage().block_time() {
+            return Err(Error::StakingStartsNow);
+        }
+        if erc20_contract_package_hash.into_hash().is_none() {
+            return Err(Error::InvalidContractPackageHash);
+        }
+        let pool = Pool::create(self.storage());
+        pool.set_name(name);
+        pool.set_address(address);
+        pool.set_erc20_contract_package_hash(erc20_contract_package_hash);
+        pool.set_staking_starts(staking_starts);
+        pool.set_staking_ends(staking_ends);
+        pool.set_withdraw_starts(withdraw_starts);
+        pool.set_withdraw_ends(withdraw_ends);
+        pool.set_staking_total(staking_total);
+        Ok(pool.id())
+    }
+
+    // Returns the number of pools; pool ids run from 0 to `pool_count() - 1`.
+    fn pool_count(&self) -> u64 {
+        Pool::count(self.storage())
+    }
+
+    /// Returns the fields of the given pool.
+    fn pool(&self, pool_id: u64) -> Result<BTreeMap<String, String>, Error> {
+        let pool = Pool::instance(self.storage(), pool_id)?;
+        let mut fields = BTreeMap::new();
+        fields.insert(NAME.to_string(), pool.name());
+        fields.insert(ADDRESS.to_string(), pool.address());
+        fields.insert(
+            ERC20_CONTRACT_PACKAGE_HASH.to_string(),
+            pool.erc20_contract_package_hash().to_formatted_string(),
+        );
+        fields.insert(
+            STAKING_STARTS.to_string(),
+            pool.staking_starts().to_string(),
+        );
+        fields.insert(STAKING_ENDS.to_string(), pool.staking_ends().to_string());
+        fields.insert(
+            WITHDRAW_STARTS.to_string(),
+            pool.withdraw_starts().to_string(),
+        );
+        fields.insert(WITHDRAW_ENDS.to_string(), pool.withdraw_ends().to_string());
+        fields.insert(STAKING_TOTAL.to_string(), pool.staking_total().to_string());
+        fields.insert(TOTAL_REWARD.to_string(), pool.total_reward().to_string());
+        fields.insert(
+            EARLY_WITHDRAW_REWARD.to_string(),
+            pool.early_withdraw_reward().to_string(),
+        );
+        fields.insert(STAKED_TOTAL.to_string(), pool.staked_total().to_string());
+        fields.insert(
+            REWARD_BALANCE.to_string(),
+            pool.reward_balance().to_string(),
+        );
+        fields.insert(
+            STAKED_BALANCE.to_string(),
+            pool.staked_balance().to_string(),
+        );
+        Ok(fields)
+    }
+
+    /// Returns the amount of tokens that have been staked in the pool by the given staker.
+    fn amount_staked(&self, pool_id: u64, staker: Key) -> Result<U256, Error> {
+        Pool::instance(self.storage(), pool_id)?
+            .get_amount_staked_by_address(&staker)
+            .ok_or(Error::NotAStaker)
+    }
+
+    /// Stakes the given amount of tokens in the pool.
+    ///
+    /// Only the part of `amount` that fits under the pool's staking total is taken from the staker.
+    fn stake(&mut self, pool_id: u64, amount: U256) -> Result<U256, Error> {
+        let pool = Pool::instance(self.storage(), pool_id)?;
+        modifiers::positive(amount)?;
+        modifiers::after(self.storage(), pool.staking_starts())?;
+        modifiers::before(self.storage(), pool.staking_ends())?;
+
+        let staker_address = detail::get_immediate_caller_address(self.storage())?;
+
+        let staked_amount =
+            modifiers::within_capacity(amount, pool.staking_total(), pool.staked_total())?;
+
+        detail::transfer_from(
+            self.storage(),
+            pool.erc20_contract_package_hash(),
+            staker_address,
+            self.contract_address(),
+            staked_amount,
+        );
+
+        pool.set_staked_total(math::add(pool.staked_total(), staked_amount)?);
+        pool.set_staked_balance(math::add(pool.staked_balance(), staked_amount)?);
+        pool.add_stake(&Key::from(staker_address), &staked_amount)?;
+
+        data::emit(
+            self.storage(),
+            &StakingContractEvent::Stake {
+                token_address: pool.address(),
+                staker_address,
+                payer_address: staker_address,
+                requested_amount: amount,
+                staked_amount,
+            },
+        );
+        Ok(staked_amount)
+    }
+
+    /// Withdraws the given amount of tokens from the pool, together with the earned reward.
+    fn withdraw(&mut self, pool_id: u64, amount: U256) -> Result<U256, Error> {
+        let pool = Pool::instance(self.storage(), pool_id)?;
+        modifiers::positive(amount)?;
+        modifiers::after(self.storage(), pool.withdraw_starts())?;
+
+        let staker_address = detail::get_immediate_caller_address(self.storage())?;
+        let staked_amount = pool
+            .get_amount_staked_by_address(&Key::from(staker_address))
+            .ok_or(Error::NotAStaker)?;
+        if amount > staked_amount {
+            return Err(Error::NotRequiredStake);
+        }
+
+        // different flows depending on when staking ends
+        let state = pool.state();
+        let now = self.storage().block_time();
+        let reward = if !state.is_closed(now) {
+            let reward = state.early_withdraw_reward(amount, now)?;
+            pool.set_reward_balance(math::sub(pool.reward_balance(), reward)?);
+            pool.set_staked_balance(math::sub(pool.staked_balance(), amount)?);
+            reward
+        } else {
+            state.after_close_reward(amount)?
+        };
+
+        pool.withdraw_stake(&Key::from(staker_address), &amount)?;
+        detail::transfer(
+            self.storage(),
+            pool.erc20_contract_package_hash(),
+            staker_address,
+            math::add(amount, reward)?,
+        );
+
+        data::emit(
+            self.storage(),
+            &StakingContractEvent::PaidOut {
+                token_address: pool.address(),
+                staker_address,
+                amount,
+                reward,
+                penalty: U256::zero(),
+            },
+        );
+        Ok(amount)
+    }
+
+    /// Adds the given amount of reward tokens to the pool.
+    fn add_reward(
+        &mut self,
+        pool_id: u64,
+        reward_amount: U256,
+        withdrawable_amount: U256,
+    ) -> Result<U256, Error> {
+        let pool = Pool::instance(self.storage(), pool_id)?;
+        modifiers::before(self.storage(), pool.withdraw_starts())?;
+
+        if reward_amount.is_zero() {
+            return Err(Error::NegativeReward);
+        }
+        if withdrawable_amount > reward_amount {
+            return Err(Error::NegativeWithdrawableReward);
+        }
+
+        let account = detail::get_immediate_caller_address(self.storage())?;
+        detail::transfer_from(
+            self.storage(),
+            pool.erc20_contract_package_hash(),
+            account,
+            self.contract_address(),
+            reward_amount,
+        );
+
+        let current_total_reward = math::add(pool.total_reward(), reward_amount)?;
+        pool.set_total_reward(current_total_reward);
+        pool.set_reward_balance(current_total_reward);
+        pool.set_early_withdraw_reward(math::add(
+            pool.early_withdraw_reward(),
+            withdrawable_amount,
+        )?);
+
+        data::emit(
+            self.storage(),
+            &StakingContractEvent::AddReward {
+                token_address: pool.address(),
+                account,
+                reward_amount,
+                withdrawable_amount,
+            },
+        );
+        Ok(reward_amount)
+    }
+
+    /// Returns the reward that the given staker would receive when withdrawing from the pool now.
+    fn staker_reward(&self, pool_id: u64, staker_address: Key) -> Result<U256, Error> {
+        let pool = Pool::instance(self.storage(), pool_id)?;
+        let amount = pool
+            .get_amount_staked_by_address(&staker_address)
+            .ok_or(Error::NotAStaker)?;
+
+        Ok(pool
+            .state()
+            .accrued_reward(amount, self.storage().block_time())?)
+    }
+
+    /// Returns the address under which the factory holds the staked tokens.
+    fn contract_address(&self) -> Address {
+        Address::ContractPackage(detail::get_contract_package_hash(
+            self.storage(),
+            STACKING_CONTRACT_PACKAGE_HASH,
+        ))
+    }
+}
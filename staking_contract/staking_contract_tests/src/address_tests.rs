@@ -0,0 +1,50 @@
+use casper_types::{
+    account::AccountHash,
+    bytesrepr::{FromBytes, ToBytes},
+    CLType, CLTyped, CLValue, ContractPackageHash, Key,
+};
+use staking_contract::address::Address;
+
+fn addresses() -> Vec<Address> {
+    vec![
+        Address::Account(AccountHash::new([1u8; 32])),
+        Address::ContractPackage(ContractPackageHash::new([2u8; 32])),
+    ]
+}
+
+#[test]
+fn test_every_address_round_trips() {
+    for address in addresses() {
+        let mut bytes = address.to_bytes().unwrap();
+        assert_eq!(bytes.len(), address.serialized_length());
+        bytes.push(7u8);
+
+        let (read_back, remainder) = Address::from_bytes(&bytes).unwrap();
+        assert_eq!(read_back, address);
+        assert_eq!(remainder, [7u8]);
+    }
+}
+
+#[test]
+fn test_addresses_are_written_as_keys() {
+    assert_eq!(Address::cl_type(), CLType::Key);
+    for address in addresses() {
+        assert_eq!(
+            address.to_bytes().unwrap(),
+            Key::from(address).to_bytes().unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_every_address_round_trips_through_a_cl_value() {
+    for address in addresses() {
+        let cl_value = CLValue::from_t(address).unwrap();
+        assert_eq!(cl_value.cl_type(), &CLType::Key);
+        assert_eq!(
+            cl_value.clone().into_t::<Key>().unwrap(),
+            Key::from(address)
+        );
+        assert_eq!(cl_value.into_t::<Address>().unwrap(), address);
+    }
+}
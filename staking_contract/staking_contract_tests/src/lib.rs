@@ -1,2 +1,8 @@
 #[cfg(test)]
 pub mod staking_contract_tests;
+
+#[cfg(test)]
+mod mock_storage_tests;
+
+#[cfg(test)]
+mod address_tests;
@@ -0,0 +1,597 @@
+use casper_types::{
+    account::AccountHash, system::CallStackElement, ApiError, CLValue, ContractHash,
+    ContractPackageHash, Key, U256,
+};
+use contract_utils::{
+    set_key, AdminControl, ContractCall, ContractContext, ContractStorage, MockContractStorage,
+};
+use staking_contract::address::Address;
+use staking_contract::data::{StakePosition, POOL_MODE_FIXED_WINDOW, POOL_MODE_STREAMING};
+use staking_contract::error::Error;
+use staking_contract::staking_contract::CEP20STK;
+
+fn staking_package() -> ContractPackageHash {
+    ContractPackageHash::new([1u8; 32])
+}
+
+fn token_package() -> ContractPackageHash {
+    ContractPackageHash::new([2u8; 32])
+}
+
+// The staking contract, run on the host against an in-memory storage.
+struct Staking(MockContractStorage);
+
+impl ContractContext<MockContractStorage> for Staking {
+    fn storage(&self) -> &MockContractStorage {
+        &self.0
+    }
+}
+
+impl CEP20STK<MockContractStorage> for Staking {}
+
+// The admin control of the staking contract, with the error it reverts with on chain.
+struct Admins(MockContractStorage);
+
+impl ContractContext<MockContractStorage> for Admins {
+    fn storage(&self) -> &MockContractStorage {
+        &self.0
+    }
+}
+
+impl AdminControl<MockContractStorage> for Admins {
+    const NOT_AN_ADMIN: ApiError = ApiError::User(Error::NotAnAdmin as u16);
+}
+
+impl Staking {
+    // Installs a staking contract whose windows are 100..200 for staking and 200..300 for
+    // withdrawing.
+    fn install() -> Staking {
+        Staking::install_with(POOL_MODE_FIXED_WINDOW, U256::zero())
+    }
+
+    // Installs a staking contract with the given pool mode and reward rate, and the windows of
+    // `install`.
+    fn install_with(pool_mode: u8, reward_rate: U256) -> Staking {
+        let storage = MockContractStorage::default();
+        storage.put_named_key(
+            "stacking_contract_package_hash",
+            Key::from(staking_package()),
+        );
+        storage.put_named_key("erc20_contract_package_hash", Key::from(token_package()));
+        storage.on_call(token_package(), "decimals", |_| {
+            CLValue::from_t(9u8).unwrap()
+        });
+        let mut staking = Staking(storage);
+        staking.call_as(AccountHash::new([10u8; 32]));
+        staking
+            .init(
+                "Staking".to_string(),
+                "address".to_string(),
+                100,
+                200,
+                200,
+                300,
+                U256::from(1_000u64),
+                pool_mode,
+                reward_rate,
+                0,
+                None,
+                "STK".to_string(),
+                U256::zero(),
+                U256::zero(),
+            )
+            .unwrap();
+        staking
+    }
+
+    // Makes the given account the caller of the next calls.
+    fn call_as(&mut self, account_hash: AccountHash) {
+        self.0.set_call_stack(vec![
+            CallStackElement::Session { account_hash },
+            CallStackElement::StoredContract {
+                contract_package_hash: staking_package(),
+                contract_hash: ContractHash::new([3u8; 32]),
+            },
+        ]);
+    }
+}
+
+// Returns the amount of a transfer of the staked token to the given account.
+fn transferred(call: &ContractCall, account_hash: AccountHash) -> U256 {
+    assert_eq!(call.contract_package_hash, token_package());
+    assert_eq!(call.entry_point, "transfer");
+    let recipient: Address = call
+        .args
+        .get("recipient")
+        .cloned()
+        .unwrap()
+        .into_t()
+        .unwrap();
+    assert_eq!(recipient, Address::from(account_hash));
+    call.args.get("amount").cloned().unwrap().into_t().unwrap()
+}
+
+#[test]
+fn test_staking_contract_runs_on_mock_storage() {
+    let mut staking = Staking::install();
+    let admin = AccountHash::new([10u8; 32]);
+    let early = AccountHash::new([11u8; 32]);
+    let late = AccountHash::new([12u8; 32]);
+
+    staking.0.set_block_time(50);
+    staking.call_as(admin);
+    staking
+        .add_reward(U256::from(500u64), U256::from(100u64))
+        .unwrap();
+
+    // Staking only opens at 100.
+    staking.call_as(early);
+    assert!(matches!(
+        staking.stake(U256::from(100u64), 0, vec![]),
+        Err(Error::AfterBadTiming)
+    ));
+    staking.0.set_block_time(150);
+    staking.stake(U256::from(100u64), 0, vec![]).unwrap();
+    staking.call_as(late);
+    staking.stake(U256::from(300u64), 0, vec![]).unwrap();
+    assert_eq!(staking.staked_total(), U256::from(400u64));
+
+    // Half-way through the withdraw window, a quarter of the stakes earns an eighth of the early
+    // withdraw reward, rounded down.
+    staking.0.set_block_time(250);
+    staking.call_as(early);
+    staking.withdraw(U256::from(100u64)).unwrap();
+    let calls = staking.0.calls();
+    let paid = &calls[calls.len() - 2..];
+    assert_eq!(transferred(&paid[0], early), U256::from(100u64));
+    assert_eq!(transferred(&paid[1], early), U256::from(12u64));
+
+    // After the window closes, the last stake takes whatever reward is left.
+    staking.0.set_block_time(300);
+    staking.call_as(late);
+    assert_eq!(
+        staking.staker_reward(Key::from(late)).unwrap(),
+        U256::from(488u64)
+    );
+    staking.withdraw(U256::from(300u64)).unwrap();
+    let calls = staking.0.calls();
+    let paid = &calls[calls.len() - 2..];
+    assert_eq!(transferred(&paid[0], late), U256::from(300u64));
+    assert_eq!(transferred(&paid[1], late), U256::from(488u64));
+}
+
+#[test]
+fn test_streaming_pool_runs_on_mock_storage() {
+    let mut staking = Staking::install_with(POOL_MODE_STREAMING, U256::from(10u64));
+    let admin = AccountHash::new([10u8; 32]);
+    let first = AccountHash::new([11u8; 32]);
+    let second = AccountHash::new([12u8; 32]);
+
+    staking.0.set_block_time(50);
+    staking.call_as(admin);
+    staking
+        .add_reward(U256::from(1_000u64), U256::zero())
+        .unwrap();
+
+    staking.0.set_block_time(1_000);
+    staking.call_as(first);
+    staking.stake(U256::from(100u64), 0, vec![]).unwrap();
+    staking.call_as(second);
+    staking.stake(U256::from(300u64), 0, vec![]).unwrap();
+
+    // Four seconds stream 40 tokens, shared by stake.
+    staking.0.set_block_time(5_000);
+    assert_eq!(
+        staking.staker_reward(Key::from(second)).unwrap(),
+        U256::from(30u64)
+    );
+    staking.call_as(first);
+    assert_eq!(staking.claim_rewards().unwrap(), U256::from(10u64));
+    let calls = staking.0.calls();
+    assert_eq!(transferred(calls.last().unwrap(), first), U256::from(10u64));
+    assert_eq!(
+        staking.staker_reward(Key::from(first)).unwrap(),
+        U256::zero()
+    );
+    assert_eq!(staking.reward_balance(), U256::from(960u64));
+}
+
+#[test]
+fn test_errors_on_mock_storage() {
+    let mut staking = Staking::install();
+    let admin = AccountHash::new([10u8; 32]);
+    let staker = AccountHash::new([11u8; 32]);
+
+    staking.0.set_block_time(150);
+    staking.call_as(staker);
+    staking.stake(U256::from(100u64), 0, vec![]).unwrap();
+    staking.0.set_block_time(250);
+    assert!(matches!(
+        staking.withdraw(U256::from(101u64)),
+        Err(Error::NotRequiredStake)
+    ));
+
+    staking.call_as(admin);
+    staking.pause().unwrap();
+    staking.call_as(staker);
+    assert!(matches!(
+        staking.stake(U256::from(100u64), 0, vec![]),
+        Err(Error::ContractPaused)
+    ));
+    assert_eq!(staking.staked_total(), U256::from(100u64));
+}
+
+#[test]
+#[should_panic(expected = "reverted with")]
+fn test_reverts_panic_on_mock_storage() {
+    let staking = Staking::install();
+    staking.0.remove_named_key("erc20_contract_package_hash");
+    staking.erc20_contract_package_hash();
+}
+
+#[test]
+#[should_panic(expected = "User(51)")]
+fn test_non_admins_revert_with_not_an_admin() {
+    let mut staking = Staking::install();
+    let mut admins = Admins(MockContractStorage::default());
+    admins.0.set_call_stack(staking.0.call_stack().to_vec());
+    admins.init();
+    admins.add_admin_without_checked(Key::from(AccountHash::new([10u8; 32])));
+    admins.assert_caller_is_admin();
+
+    staking.call_as(AccountHash::new([11u8; 32]));
+    admins.0.set_call_stack(staking.0.call_stack().to_vec());
+    admins.assert_caller_is_admin();
+}
+
+#[test]
+fn test_window_ends_cannot_move_into_the_past() {
+    let mut staking = Staking::install();
+
+    staking.0.set_block_time(150);
+    assert!(matches!(
+        staking.update_staking_ends(120),
+        Err(Error::WindowEndsInPast)
+    ));
+    staking.update_staking_ends(180).unwrap();
+    assert_eq!(staking.staking_ends(), 180);
+    assert_eq!(staking.withdraw_starts(), 180);
+
+    staking.0.set_block_time(250);
+    assert!(matches!(
+        staking.update_withdraw_ends(240),
+        Err(Error::WindowEndsInPast)
+    ));
+    staking.update_withdraw_ends(400).unwrap();
+    assert_eq!(staking.withdraw_ends(), 400);
+}
+
+#[test]
+fn test_compound_after_staking_ends_keeps_the_shares_of_others() {
+    let mut staking = Staking::install();
+    let admin = AccountHash::new([10u8; 32]);
+    let compounder = AccountHash::new([11u8; 32]);
+    let other = AccountHash::new([12u8; 32]);
+
+    staking.0.set_block_time(50);
+    staking.call_as(admin);
+    staking
+        .add_reward(U256::from(500u64), U256::from(100u64))
+        .unwrap();
+    staking.0.set_block_time(150);
+    staking.call_as(compounder);
+    staking.stake(U256::from(100u64), 0, vec![]).unwrap();
+    staking.call_as(other);
+    staking.stake(U256::from(300u64), 0, vec![]).unwrap();
+
+    // Half-way through the withdraw window, a quarter of the stakes has earned an eighth of the
+    // early withdraw reward.
+    staking.0.set_block_time(250);
+    staking.call_as(compounder);
+    assert_eq!(staking.compound().unwrap(), U256::from(12u64));
+    assert_eq!(
+        staking.balance_of(Key::from(compounder)),
+        U256::from(112u64)
+    );
+    assert_eq!(staking.weighted_total(), U256::from(400u64));
+
+    // Once the window closes, the other stake still takes three quarters of the reward balance.
+    staking.0.set_block_time(300);
+    assert_eq!(
+        staking.staker_reward(Key::from(other)).unwrap(),
+        U256::from(375u64)
+    );
+    assert_eq!(
+        staking.staker_reward(Key::from(compounder)).unwrap(),
+        U256::from(113u64)
+    );
+}
+
+#[test]
+fn test_emergency_withdraw_streams_the_given_up_reward_to_others() {
+    let mut staking = Staking::install_with(POOL_MODE_STREAMING, U256::from(10u64));
+    let admin = AccountHash::new([10u8; 32]);
+    let leaving = AccountHash::new([11u8; 32]);
+    let staying = AccountHash::new([12u8; 32]);
+
+    staking.0.set_block_time(50);
+    staking.call_as(admin);
+    staking
+        .add_reward(U256::from(1_000u64), U256::zero())
+        .unwrap();
+    staking.0.set_block_time(1_000);
+    staking.call_as(leaving);
+    staking.stake(U256::from(100u64), 0, vec![]).unwrap();
+    staking.call_as(staying);
+    staking.stake(U256::from(300u64), 0, vec![]).unwrap();
+
+    // Four seconds stream 40 tokens; the 10 earned by the leaving stake stream again.
+    staking.0.set_block_time(5_000);
+    staking.call_as(admin);
+    staking.pause().unwrap();
+    staking.call_as(leaving);
+    assert_eq!(staking.emergency_withdraw().unwrap(), U256::from(100u64));
+    let calls = staking.0.calls();
+    assert_eq!(
+        transferred(calls.last().unwrap(), leaving),
+        U256::from(100u64)
+    );
+    assert_eq!(staking.reward_balance(), U256::from(970u64));
+    assert_eq!(staking.weighted_balance(), U256::from(300u64));
+
+    // The stake that stays keeps its 30 and gets the whole stream from now on.
+    staking.0.set_block_time(8_000);
+    assert_eq!(
+        staking.staker_reward(Key::from(staying)).unwrap(),
+        U256::from(60u64)
+    );
+}
+
+#[test]
+fn test_emergency_withdraw_after_close_leaves_the_reward_to_others() {
+    let mut staking = Staking::install();
+    let admin = AccountHash::new([10u8; 32]);
+    let withdrawing = AccountHash::new([11u8; 32]);
+    let locked = AccountHash::new([12u8; 32]);
+    let staying = AccountHash::new([13u8; 32]);
+
+    staking.0.set_block_time(50);
+    staking.call_as(admin);
+    staking.set_lock_tier(10_000, 20_000).unwrap();
+    staking
+        .add_reward(U256::from(500u64), U256::zero())
+        .unwrap();
+    staking.0.set_block_time(150);
+    staking.call_as(withdrawing);
+    staking.stake(U256::from(100u64), 0, vec![]).unwrap();
+    staking.call_as(locked);
+    staking.stake(U256::from(100u64), 10_000, vec![]).unwrap();
+    staking.call_as(staying);
+    staking.stake(U256::from(200u64), 0, vec![]).unwrap();
+
+    // A fifth of the weight takes a fifth of the reward balance.
+    staking.0.set_block_time(300);
+    staking.call_as(withdrawing);
+    staking.withdraw(U256::from(100u64)).unwrap();
+    assert_eq!(staking.reward_balance(), U256::from(400u64));
+    assert_eq!(staking.weighted_balance(), U256::from(400u64));
+
+    // The locked stake leaves with its principal only, and its share stays in the balance.
+    staking.call_as(locked);
+    assert!(matches!(
+        staking.withdraw(U256::from(100u64)),
+        Err(Error::StakeLocked)
+    ));
+    assert_eq!(staking.emergency_withdraw().unwrap(), U256::from(100u64));
+    let calls = staking.0.calls();
+    assert_eq!(
+        transferred(calls.last().unwrap(), locked),
+        U256::from(100u64)
+    );
+    assert_eq!(staking.unlock_time(Key::from(locked)), 0);
+    assert_eq!(staking.reward_balance(), U256::from(400u64));
+    assert_eq!(staking.weighted_balance(), U256::from(200u64));
+
+    // The last stake takes all that is left.
+    assert_eq!(
+        staking.staker_reward(Key::from(staying)).unwrap(),
+        U256::from(400u64)
+    );
+}
+
+#[test]
+fn test_stake_for_cannot_lock_the_stake_of_someone_else() {
+    let mut staking = Staking::install();
+    let admin = AccountHash::new([10u8; 32]);
+    let staker = AccountHash::new([11u8; 32]);
+    let third_party = AccountHash::new([12u8; 32]);
+
+    staking.call_as(admin);
+    staking.set_lock_tier(10_000, 20_000).unwrap();
+    staking.0.set_block_time(150);
+    staking.call_as(staker);
+    staking.stake(U256::from(100u64), 0, vec![]).unwrap();
+
+    // One token with the longest lock would hold back the whole stake.
+    staking.call_as(third_party);
+    assert!(matches!(
+        staking.stake_for(Address::from(staker), U256::one(), 10_000, vec![]),
+        Err(Error::LockForAnotherStaker)
+    ));
+    staking
+        .stake_for(Address::from(staker), U256::one(), 0, vec![])
+        .unwrap();
+    assert_eq!(staking.unlock_time(Key::from(staker)), 0);
+    assert_eq!(staking.balance_of(Key::from(staker)), U256::from(101u64));
+
+    // The staker can still lock their own stake.
+    staking.call_as(staker);
+    staking
+        .stake_for(Address::from(staker), U256::one(), 10_000, vec![])
+        .unwrap();
+    assert_eq!(staking.unlock_time(Key::from(staker)), 10_150);
+}
+
+#[test]
+fn test_transfer_stake_pays_out_the_streamed_reward_of_a_whole_stake() {
+    let mut staking = Staking::install_with(POOL_MODE_STREAMING, U256::from(10u64));
+    let admin = AccountHash::new([10u8; 32]);
+    let staker = AccountHash::new([11u8; 32]);
+    let recipient = AccountHash::new([12u8; 32]);
+    let locked = AccountHash::new([13u8; 32]);
+
+    staking.0.set_block_time(50);
+    staking.call_as(admin);
+    staking.set_lock_tier(10_000, 10_000).unwrap();
+    staking
+        .add_reward(U256::from(1_000u64), U256::zero())
+        .unwrap();
+    staking.0.set_block_time(1_000);
+    staking.call_as(staker);
+    staking.stake(U256::from(100u64), 0, vec![]).unwrap();
+    staking.call_as(locked);
+    staking.stake(U256::from(100u64), 10_000, vec![]).unwrap();
+
+    // A locked stake can't move, so its lock can't hold back the stake of the recipient.
+    staking.0.set_block_time(5_000);
+    assert!(matches!(
+        staking.transfer_stake(Address::from(recipient), U256::from(100u64)),
+        Err(Error::StakeLocked)
+    ));
+
+    // Four seconds stream 40 tokens, half of which the staker earned before moving away.
+    staking.call_as(staker);
+    staking
+        .transfer_stake(Address::from(recipient), U256::from(100u64))
+        .unwrap();
+    let calls = staking.0.calls();
+    assert_eq!(
+        transferred(calls.last().unwrap(), staker),
+        U256::from(20u64)
+    );
+    assert_eq!(staking.balance_of(Key::from(staker)), U256::zero());
+
+    // The recipient earns on the stake from now on.
+    assert_eq!(
+        staking.staker_reward(Key::from(recipient)).unwrap(),
+        U256::zero()
+    );
+    staking.0.set_block_time(8_000);
+    assert_eq!(
+        staking.staker_reward(Key::from(recipient)).unwrap(),
+        U256::from(15u64)
+    );
+}
+
+#[test]
+fn test_roll_stake_checks_the_allowlist_and_limits_of_the_new_epoch() {
+    let mut staking = Staking::install();
+    let admin = AccountHash::new([10u8; 32]);
+    let staker = AccountHash::new([11u8; 32]);
+
+    staking.0.set_block_time(150);
+    staking.call_as(staker);
+    staking.stake(U256::from(300u64), 0, vec![]).unwrap();
+
+    staking.0.set_block_time(300);
+    staking.call_as(admin);
+    staking
+        .schedule_epoch(400, 500, 500, 600, U256::from(1_000u64))
+        .unwrap();
+    staking.set_allowlist_enabled(true);
+    staking
+        .update_stake_limits(U256::zero(), U256::from(200u64))
+        .unwrap();
+
+    staking.0.set_block_time(450);
+    staking.call_as(staker);
+    assert!(matches!(
+        staking.roll_stake(vec![]),
+        Err(Error::NotAllowlisted)
+    ));
+    staking.call_as(admin);
+    staking.add_to_allowlist(vec![Key::from(staker)]);
+    staking.call_as(staker);
+    assert!(matches!(
+        staking.roll_stake(vec![]),
+        Err(Error::AboveMaxStake)
+    ));
+
+    staking.call_as(admin);
+    staking
+        .update_stake_limits(U256::zero(), U256::from(300u64))
+        .unwrap();
+    staking.call_as(staker);
+    assert_eq!(staking.roll_stake(vec![]).unwrap(), U256::from(300u64));
+    assert_eq!(staking.staked_total(), U256::from(300u64));
+}
+
+#[test]
+fn test_withdraw_settles_positions_only_once_it_is_allowed() {
+    let mut staking = Staking::install();
+    let staker = AccountHash::new([11u8; 32]);
+    let position = |amount: u64, withdrawn: u64| StakePosition {
+        amount: U256::from(amount),
+        withdrawn: U256::from(withdrawn),
+    };
+
+    staking.0.set_block_time(150);
+    staking.call_as(staker);
+    for amount in [100u64, 200, 300] {
+        staking.stake(U256::from(amount), 0, vec![]).unwrap();
+    }
+
+    // A withdraw that isn't allowed yet leaves the positions alone.
+    staking.0.set_block_time(180);
+    assert!(matches!(
+        staking.withdraw(U256::from(50u64)),
+        Err(Error::AfterBadTiming)
+    ));
+    assert!(matches!(
+        staking.withdraw_position(1, U256::from(50u64)),
+        Err(Error::AfterBadTiming)
+    ));
+    assert_eq!(
+        staking.positions(Key::from(staker)),
+        vec![position(100, 0), position(200, 0), position(300, 0)]
+    );
+
+    // The oldest positions go first, skipping the ones already emptied.
+    staking.0.set_block_time(250);
+    staking.withdraw_position(1, U256::from(200u64)).unwrap();
+    staking.withdraw(U256::from(150u64)).unwrap();
+    assert_eq!(
+        staking.positions(Key::from(staker)),
+        vec![position(100, 100), position(200, 200), position(300, 50)]
+    );
+    staking.withdraw(U256::from(250u64)).unwrap();
+    assert_eq!(
+        staking.positions(Key::from(staker)),
+        vec![position(100, 100), position(200, 200), position(300, 300)]
+    );
+    assert!(matches!(
+        staking.withdraw(U256::from(1u64)),
+        Err(Error::NotRequiredStake)
+    ));
+}
+
+#[test]
+fn test_migrate_keeps_the_state_of_the_current_version() {
+    let mut staking = Staking::install();
+    let staker = AccountHash::new([11u8; 32]);
+
+    staking.0.set_block_time(150);
+    staking.call_as(staker);
+    staking.stake(U256::from(100u64), 0, vec![]).unwrap();
+    staking.migrate().unwrap();
+    assert_eq!(staking.schema_version(), 1);
+    assert_eq!(staking.staked_total(), U256::from(100u64));
+    assert_eq!(staking.balance_of(Key::from(staker)), U256::from(100u64));
+
+    // State written by newer code is left alone.
+    set_key(&staking.0, "schema_version", 2u32);
+    assert!(matches!(
+        staking.migrate(),
+        Err(Error::UnknownSchemaVersion)
+    ));
+}
@@ -8,8 +8,10 @@ use casper_engine_test_support::{
 };
 use casper_execution_engine::storage::global_state::in_memory::InMemoryGlobalState;
 use casper_types::{
-    account::AccountHash, bytesrepr::ToBytes, runtime_args, ContractHash, ContractPackageHash, Key,
-    PublicKey, RuntimeArgs, SecretKey, BLAKE2B_DIGEST_LENGTH, U256,
+    account::AccountHash,
+    bytesrepr::{Bytes, FromBytes, ToBytes},
+    runtime_args, ContractHash, ContractPackageHash, Key, PublicKey, RuntimeArgs, SecretKey,
+    BLAKE2B_DIGEST_LENGTH, U256,
 };
 use once_cell::sync::Lazy;
 use std::convert::TryInto;
@@ -22,8 +24,14 @@ const STAKING_WASM: &str = "staking_contract.wasm";
 const ERC20_CONTRACT_NAME: &str = "erc20_token_contract";
 const ERC20_CONTRACT_PACKAGE_HASH: &str = "erc20-contract_package_hash";
 const STAKING_CONTRACT_HASH: &str = "staking_contract_hash";
-const STAKING_CONTRACT_PACKAGE_HASH: &str = "contract_package_hash";
+const STAKING_CONTRACT_PACKAGE_HASH: &str = "staking_contract_package_hash";
 const ALLOWANCES_SEED_UREF: &str = "allowances";
+const STAKING_FACTORY_WASM: &str = "staking_factory.wasm";
+const STAKING_FACTORY_CONTRACT_HASH: &str = "staking_factory_contract_hash";
+const STAKING_FACTORY_PACKAGE_HASH: &str = "staking_factory_package_hash";
+const STAKING_PROXY_WASM: &str = "staking_proxy.wasm";
+const STAKING_PROXY_PACKAGE_HASH: &str = "staking_proxy_package_hash";
+const STAKING_PROXY_CONTRACT_HASH: &str = "staking_proxy_contract_hash";
 
 #[test]
 fn test_approve_and_stake() {
@@ -1510,6 +1518,2707 @@ fn test_approve_and_stake_and_add_reward_withdrawable_amount_too_big() {
     builder.exec(add_reward_request).expect_success().commit();
 }
 
+#[test]
+fn test_factory_create_pool_and_stake() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let factory_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_FACTORY_WASM,
+        runtime_args! {},
+    )
+    .build();
+
+    builder
+        .exec(factory_install_request)
+        .expect_success()
+        .commit();
+
+    let factory_contract_hash = get_contract_hash(&builder, STAKING_FACTORY_CONTRACT_HASH);
+    let factory_package_hash = get_contract_package_hash(&builder, STAKING_FACTORY_PACKAGE_HASH);
+    let factory_key: Key = factory_package_hash.into();
+
+    for (name, staking_total) in [("FerrumX", 500000i64), ("FerrumY", 3i64)] {
+        let create_pool_args = runtime_args! {
+            "name" => name.to_string(),
+            "address" => ADDRESS.to_string(),
+            "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+            "staking_starts" => 0u64,
+            "staking_ends" => 1781708875776u64,
+            "withdraw_starts" => 1781708875776u64,
+            "withdraw_ends" => 1781708875786u64,
+            "staking_total" => U256::from(staking_total),
+        };
+
+        let create_pool_request = ExecuteRequestBuilder::contract_call_by_hash(
+            *DEFAULT_ACCOUNT_ADDR,
+            factory_contract_hash,
+            "create_pool",
+            create_pool_args,
+        )
+        .build();
+
+        builder.exec(create_pool_request).expect_success().commit();
+    }
+
+    let approve_args = runtime_args! {
+        "spender" => factory_key,
+        "amount" => U256::from(10i64),
+    };
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        approve_args,
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    // The second pool only has room for 3 tokens.
+    let stake_args = runtime_args! {
+        "pool_id" => 1u64,
+        "amount" => U256::from(5i64),
+    };
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        factory_contract_hash,
+        "stake",
+        stake_args,
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let pool_stakes_uref = builder
+        .query(None, Key::from(factory_contract_hash), &[])
+        .unwrap()
+        .as_contract()
+        .expect("must have factory contract")
+        .named_keys()
+        .get("pool_stakes")
+        .expect("must have pool_stakes entry")
+        .as_uref()
+        .expect("must be a uref")
+        .to_owned();
+
+    let mut preimage = Key::from(*DEFAULT_ACCOUNT_ADDR).to_bytes().unwrap();
+    preimage.append(&mut 1u64.to_bytes().unwrap());
+    let staked: Option<U256> = builder
+        .query_dictionary_item(
+            None,
+            pool_stakes_uref,
+            &hex::encode(create_blake2b_hash(preimage)),
+        )
+        .expect("should be stored value.")
+        .as_cl_value()
+        .expect("should be cl value.")
+        .clone()
+        .into_t()
+        .expect("must convert to Option<U256>");
+    assert_eq!(staked, Some(U256::from(3i64)));
+
+    let erc20_contract_key: Key = erc20_contract_hash.into();
+    let balance = balance_dictionary(&builder, erc20_contract_key, factory_key);
+    assert_eq!(balance, U256::from(3i64));
+}
+
+#[test]
+fn test_add_reward_in_separate_reward_token() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    // Install the staked token and the reward token, keeping the hashes of each before the next
+    // install overwrites the named keys.
+    let mut tokens = Vec::new();
+    for (name, symbol) in [("FERRUM_ERC20", "F_ERC20"), ("REWARD_ERC20", "R_ERC20")] {
+        let erc20_runtime_args = runtime_args! {
+            "name" => name.to_string(),
+            "symbol" => symbol.to_string(),
+            "total_supply" => U256::from(500000i64),
+            "decimals" => 8u8,
+        };
+
+        let erc_20_install_request =
+            ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+                .build();
+
+        builder
+            .exec(erc_20_install_request)
+            .expect_success()
+            .commit();
+
+        tokens.push((
+            get_erc20_contract_hash(&builder),
+            get_erc20_contract_package_hash(&builder),
+        ));
+    }
+    let (_, erc20_contract_package_hash) = tokens[0];
+    let (reward_contract_hash, reward_contract_package_hash) = tokens[1];
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+        "reward_token_package_hash" => Key::from(reward_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        reward_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let add_reward_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "add_reward",
+        runtime_args! {
+            "reward_amount" => U256::from(4i64),
+            "withdrawable_amount" => U256::from(2i64),
+        },
+    )
+    .build();
+
+    builder.exec(add_reward_request).expect_success().commit();
+
+    let reward_contract_key: Key = reward_contract_hash.into();
+    let balance = balance_dictionary(&builder, reward_contract_key, staking_contract_key);
+    assert_eq!(balance, U256::from(4i64));
+}
+
+#[test]
+fn test_streaming_stake_and_withdraw() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    // One reward token streams every second.
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+        "pool_mode" => 1u8,
+        "reward_rate" => U256::from(1i64),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(60i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let add_reward_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "add_reward",
+        runtime_args! {
+            "reward_amount" => U256::from(50i64),
+            "withdrawable_amount" => U256::from(0i64),
+        },
+    )
+    .build();
+
+    builder.exec(add_reward_request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(10i64),
+        },
+    )
+    .with_block_time(1_000_000u64)
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    // Withdrawing outside of the withdraw window pays the 10 seconds of reward streamed so far.
+    let withdraw_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "withdraw",
+        runtime_args! {
+            "amount" => U256::from(10i64),
+        },
+    )
+    .with_block_time(1_010_000u64)
+    .build();
+
+    builder.exec(withdraw_request).expect_success().commit();
+
+    let erc20_contract_key: Key = erc20_contract_hash.into();
+    let balance = balance_dictionary(&builder, erc20_contract_key, staking_contract_key);
+    assert_eq!(balance, U256::from(40i64));
+}
+
+#[test]
+fn test_claim_rewards_keeps_stake() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let add_reward_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "add_reward",
+        runtime_args! {
+            "reward_amount" => U256::from(4i64),
+            "withdrawable_amount" => U256::from(2i64),
+        },
+    )
+    .build();
+
+    builder.exec(add_reward_request).expect_success().commit();
+
+    let claim_rewards_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "claim_rewards",
+        runtime_args! {},
+    )
+    .with_block_time(1781708875786u64)
+    .build();
+
+    builder
+        .exec(claim_rewards_request)
+        .expect_success()
+        .commit();
+
+    // The whole reward went out, the principal stayed.
+    let erc20_contract_key: Key = erc20_contract_hash.into();
+    let balance = balance_dictionary(&builder, erc20_contract_key, staking_contract_key);
+    assert_eq!(balance, U256::from(5i64));
+
+    // Nothing is left to claim.
+    let claim_rewards_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "claim_rewards",
+        runtime_args! {},
+    )
+    .with_block_time(1781708875786u64)
+    .build();
+
+    builder.exec(claim_rewards_request).expect_failure();
+}
+
+#[test]
+fn test_compound_moves_reward_into_stake() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let add_reward_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "add_reward",
+        runtime_args! {
+            "reward_amount" => U256::from(4i64),
+            "withdrawable_amount" => U256::from(2i64),
+        },
+    )
+    .build();
+
+    builder.exec(add_reward_request).expect_success().commit();
+
+    // Compounding works after staking ends.
+    let compound_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "compound",
+        runtime_args! {},
+    )
+    .with_block_time(1781708875786u64)
+    .build();
+
+    builder.exec(compound_request).expect_success().commit();
+
+    let staked = amount_staked_dictionary(
+        &builder,
+        staking_contract_hash,
+        Key::Account(*DEFAULT_ACCOUNT_ADDR),
+    );
+    assert_eq!(staked, U256::from(9i64));
+
+    // No tokens left the contract.
+    let erc20_contract_key: Key = erc20_contract_hash.into();
+    let balance = balance_dictionary(&builder, erc20_contract_key, staking_contract_key);
+    assert_eq!(balance, U256::from(9i64));
+}
+
+#[test]
+fn test_pause_and_emergency_withdraw() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let pause_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "pause",
+        runtime_args! {},
+    )
+    .build();
+
+    builder.exec(pause_request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_failure();
+
+    let emergency_withdraw_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "emergency_withdraw",
+        runtime_args! {},
+    )
+    .build();
+
+    builder
+        .exec(emergency_withdraw_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_key: Key = erc20_contract_hash.into();
+    let balance = balance_dictionary(&builder, erc20_contract_key, staking_contract_key);
+    assert_eq!(balance, U256::zero());
+}
+
+#[test]
+fn test_locked_stake_cannot_be_withdrawn_before_unlock() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    // The tier locks stakes until after the withdraw window closes.
+    let set_lock_tier_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "set_lock_tier",
+        runtime_args! {
+            "lock_duration" => 1781708875800u64,
+            "multiplier" => 20000u32,
+        },
+    )
+    .build();
+
+    builder
+        .exec(set_lock_tier_request)
+        .expect_success()
+        .commit();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+            "lock_duration" => 1781708875800u64,
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let add_reward_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "add_reward",
+        runtime_args! {
+            "reward_amount" => U256::from(4i64),
+            "withdrawable_amount" => U256::from(2i64),
+        },
+    )
+    .build();
+
+    builder.exec(add_reward_request).expect_success().commit();
+
+    let withdraw_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "withdraw",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .with_block_time(1781708875786u64)
+    .build();
+
+    builder.exec(withdraw_request).expect_failure();
+
+    let withdraw_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "withdraw",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .with_block_time(1781708875800u64)
+    .build();
+
+    builder.exec(withdraw_request).expect_success().commit();
+
+    // Principal and the whole reward went out.
+    let erc20_contract_key: Key = erc20_contract_hash.into();
+    let balance = balance_dictionary(&builder, erc20_contract_key, staking_contract_key);
+    assert_eq!(balance, U256::zero());
+}
+
+#[test]
+fn test_withdraw_position_takes_from_one_position() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    // Two stakes open positions 0 and 1.
+    for amount in [3i64, 4i64] {
+        let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+            *DEFAULT_ACCOUNT_ADDR,
+            staking_contract_hash,
+            "stake",
+            runtime_args! {
+                "amount" => U256::from(amount),
+            },
+        )
+        .build();
+
+        builder.exec(stake_request).expect_success().commit();
+    }
+
+    let withdraw_position_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "withdraw_position",
+        runtime_args! {
+            "position_id" => 1u64,
+            "amount" => U256::from(4i64),
+        },
+    )
+    .with_block_time(1781708875786u64)
+    .build();
+
+    builder
+        .exec(withdraw_position_request)
+        .expect_success()
+        .commit();
+
+    // Position 1 is empty now, and position 2 was never opened.
+    for position_id in [1u64, 2u64] {
+        let withdraw_position_request = ExecuteRequestBuilder::contract_call_by_hash(
+            *DEFAULT_ACCOUNT_ADDR,
+            staking_contract_hash,
+            "withdraw_position",
+            runtime_args! {
+                "position_id" => position_id,
+                "amount" => U256::from(1i64),
+            },
+        )
+        .with_block_time(1781708875786u64)
+        .build();
+
+        builder.exec(withdraw_position_request).expect_failure();
+    }
+
+    let staker = Key::from(*DEFAULT_ACCOUNT_ADDR);
+    let amount_staked = amount_staked_dictionary(&builder, staking_contract_hash, staker);
+    assert_eq!(amount_staked, U256::from(3i64));
+}
+
+#[test]
+fn test_early_withdraw_penalty_goes_to_treasury() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let treasury = Key::Account(AccountHash::new([7u8; 32]));
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+        "early_withdraw_penalty" => 1000u32,
+        "penalty_treasury" => treasury,
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let withdraw_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "withdraw",
+        runtime_args! {
+            "amount" => U256::from(10i64),
+        },
+    )
+    .with_block_time(1781708875779u64)
+    .build();
+
+    builder.exec(withdraw_request).expect_success().commit();
+
+    // 10% of the principal went to the treasury, the rest back to the staker.
+    let erc20_contract_key: Key = erc20_contract_hash.into();
+    let treasury_balance = balance_dictionary(&builder, erc20_contract_key, treasury);
+    assert_eq!(treasury_balance, U256::from(1i64));
+    let staking_balance = balance_dictionary(&builder, erc20_contract_key, staking_contract_key);
+    assert_eq!(staking_balance, U256::zero());
+}
+
+#[test]
+fn test_stake_appends_ces_event() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    assert_eq!(events_length(&builder, staking_contract_hash), 0);
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    assert_eq!(events_length(&builder, staking_contract_hash), 1);
+
+    // The event starts with its prefixed name, followed by its fields.
+    let event = event_dictionary(&builder, staking_contract_hash, 0);
+    let (event_name, fields) = String::from_bytes(&event).unwrap();
+    assert_eq!(event_name, "event_Stake");
+    let (token_address, fields) = String::from_bytes(fields).unwrap();
+    assert_eq!(token_address, ADDRESS);
+    let (staker_address, fields) = Key::from_bytes(fields).unwrap();
+    assert_eq!(staker_address, Key::from(*DEFAULT_ACCOUNT_ADDR));
+    let (payer_address, fields) = Key::from_bytes(fields).unwrap();
+    assert_eq!(payer_address, Key::from(*DEFAULT_ACCOUNT_ADDR));
+    let (requested_amount, fields) = U256::from_bytes(fields).unwrap();
+    let (staked_amount, fields) = U256::from_bytes(fields).unwrap();
+    assert_eq!(requested_amount, U256::from(5i64));
+    assert_eq!(staked_amount, U256::from(5i64));
+    assert!(fields.is_empty());
+}
+
+#[test]
+fn test_refund_and_add_reward_emit_events() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(10i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(30i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    // The first 8 tokens fit under the staking total, then only 2 of the next 8 do and the rest
+    // is refunded.
+    for _ in 0..2 {
+        let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+            *DEFAULT_ACCOUNT_ADDR,
+            staking_contract_hash,
+            "stake",
+            runtime_args! {
+                "amount" => U256::from(8i64),
+            },
+        )
+        .build();
+
+        builder.exec(stake_request).expect_success().commit();
+    }
+
+    let erc20_contract_key: Key = erc20_contract_hash.into();
+    let balance = balance_dictionary(&builder, erc20_contract_key, staking_contract_key);
+    assert_eq!(balance, U256::from(10i64));
+    let balance = balance_dictionary(
+        &builder,
+        erc20_contract_key,
+        Key::from(*DEFAULT_ACCOUNT_ADDR),
+    );
+    assert_eq!(balance, U256::from(499990i64));
+
+    let add_reward_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "add_reward",
+        runtime_args! {
+            "reward_amount" => U256::from(4i64),
+            "withdrawable_amount" => U256::from(2i64),
+        },
+    )
+    .build();
+
+    builder.exec(add_reward_request).expect_success().commit();
+
+    // Stake, Stake, Refunded, AddReward.
+    assert_eq!(events_length(&builder, staking_contract_hash), 4);
+
+    let event = event_dictionary(&builder, staking_contract_hash, 1);
+    let (event_name, fields) = String::from_bytes(&event).unwrap();
+    assert_eq!(event_name, "event_Stake");
+    let (_token_address, fields) = String::from_bytes(fields).unwrap();
+    let (_staker_address, fields) = Key::from_bytes(fields).unwrap();
+    let (_payer_address, fields) = Key::from_bytes(fields).unwrap();
+    let (requested_amount, fields) = U256::from_bytes(fields).unwrap();
+    let (staked_amount, fields) = U256::from_bytes(fields).unwrap();
+    assert_eq!(requested_amount, U256::from(8i64));
+    assert_eq!(staked_amount, U256::from(2i64));
+    assert!(fields.is_empty());
+
+    let event = event_dictionary(&builder, staking_contract_hash, 2);
+    let (event_name, fields) = String::from_bytes(&event).unwrap();
+    assert_eq!(event_name, "event_Refunded");
+    let (token_address, fields) = String::from_bytes(fields).unwrap();
+    assert_eq!(token_address, ADDRESS);
+    let (staker_address, fields) = Key::from_bytes(fields).unwrap();
+    assert_eq!(staker_address, Key::from(*DEFAULT_ACCOUNT_ADDR));
+    let (amount, fields) = U256::from_bytes(fields).unwrap();
+    assert_eq!(amount, U256::from(6i64));
+    assert!(fields.is_empty());
+
+    let event = event_dictionary(&builder, staking_contract_hash, 3);
+    let (event_name, fields) = String::from_bytes(&event).unwrap();
+    assert_eq!(event_name, "event_AddReward");
+    let (token_address, fields) = String::from_bytes(fields).unwrap();
+    assert_eq!(token_address, ADDRESS);
+    let (account, fields) = Key::from_bytes(fields).unwrap();
+    assert_eq!(account, Key::from(*DEFAULT_ACCOUNT_ADDR));
+    let (reward_amount, fields) = U256::from_bytes(fields).unwrap();
+    let (withdrawable_amount, fields) = U256::from_bytes(fields).unwrap();
+    assert_eq!(reward_amount, U256::from(4i64));
+    assert_eq!(withdrawable_amount, U256::from(2i64));
+    assert!(fields.is_empty());
+}
+
+#[test]
+fn test_stake_for_credits_beneficiary() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let beneficiary = Key::Account(AccountHash::new([3u8; 32]));
+
+    let stake_for_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake_for",
+        runtime_args! {
+            "beneficiary" => beneficiary,
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_for_request).expect_success().commit();
+
+    // The tokens came from the caller, the stake belongs to the beneficiary.
+    let amount_staked = amount_staked_dictionary(&builder, staking_contract_hash, beneficiary);
+    assert_eq!(amount_staked, U256::from(5i64));
+    let erc20_contract_key: Key = erc20_contract_hash.into();
+    let balance = balance_dictionary(&builder, erc20_contract_key, staking_contract_key);
+    assert_eq!(balance, U256::from(5i64));
+
+    let event = event_dictionary(&builder, staking_contract_hash, 0);
+    let (event_name, fields) = String::from_bytes(&event).unwrap();
+    assert_eq!(event_name, "event_Stake");
+    let (_token_address, fields) = String::from_bytes(fields).unwrap();
+    let (staker_address, fields) = Key::from_bytes(fields).unwrap();
+    assert_eq!(staker_address, beneficiary);
+    let (payer_address, _) = Key::from_bytes(fields).unwrap();
+    assert_eq!(payer_address, Key::from(*DEFAULT_ACCOUNT_ADDR));
+}
+
+#[test]
+fn test_stored_contract_stakes_and_withdraws() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let staking_proxy_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_PROXY_WASM,
+        runtime_args! {},
+    )
+    .build();
+
+    builder
+        .exec(staking_proxy_install_request)
+        .expect_success()
+        .commit();
+
+    let proxy_contract_hash = get_contract_hash(&builder, STAKING_PROXY_CONTRACT_HASH);
+    let proxy_key: Key = get_contract_package_hash(&builder, STAKING_PROXY_PACKAGE_HASH).into();
+
+    // The proxy holds the tokens it stakes.
+    let transfer_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "transfer",
+        runtime_args! {
+            "recipient" => proxy_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(transfer_request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        proxy_contract_hash,
+        "stake",
+        runtime_args! {
+            "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+            "stacking_contract_package_hash" => staking_contract_key,
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    // The stake belongs to the proxy, not to the account that called it.
+    let amount_staked = amount_staked_dictionary(&builder, staking_contract_hash, proxy_key);
+    assert_eq!(amount_staked, U256::from(5i64));
+
+    let withdraw_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        proxy_contract_hash,
+        "withdraw",
+        runtime_args! {
+            "stacking_contract_package_hash" => staking_contract_key,
+            "amount" => U256::from(5i64),
+        },
+    )
+    .with_block_time(1781708875786u64)
+    .build();
+
+    builder.exec(withdraw_request).expect_success().commit();
+
+    let erc20_contract_key: Key = erc20_contract_hash.into();
+    let proxy_balance = balance_dictionary(&builder, erc20_contract_key, proxy_key);
+    assert_eq!(proxy_balance, U256::from(10i64));
+
+    // Stake and PaidOut both name the proxy as the staker.
+    assert_eq!(events_length(&builder, staking_contract_hash), 2);
+    for index in 0..2 {
+        let event = event_dictionary(&builder, staking_contract_hash, index);
+        let (_event_name, fields) = String::from_bytes(&event).unwrap();
+        let (_token_address, fields) = String::from_bytes(fields).unwrap();
+        let (staker_address, _) = Key::from_bytes(fields).unwrap();
+        assert_eq!(staker_address, proxy_key);
+    }
+}
+
+#[test]
+fn test_transfer_stake_moves_stake_without_tokens() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let recipient = Key::Account(AccountHash::new([4u8; 32]));
+
+    let transfer_stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "transfer_stake",
+        runtime_args! {
+            "recipient" => recipient,
+            "amount" => U256::from(3i64),
+        },
+    )
+    .build();
+
+    builder
+        .exec(transfer_stake_request)
+        .expect_success()
+        .commit();
+
+    let staker = Key::from(*DEFAULT_ACCOUNT_ADDR);
+    let amount_staked = amount_staked_dictionary(&builder, staking_contract_hash, staker);
+    assert_eq!(amount_staked, U256::from(2i64));
+    let amount_staked = amount_staked_dictionary(&builder, staking_contract_hash, recipient);
+    assert_eq!(amount_staked, U256::from(3i64));
+
+    // No tokens moved.
+    let erc20_contract_key: Key = erc20_contract_hash.into();
+    let balance = balance_dictionary(&builder, erc20_contract_key, staking_contract_key);
+    assert_eq!(balance, U256::from(5i64));
+
+    // Only 2 tokens are left to move.
+    let transfer_stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "transfer_stake",
+        runtime_args! {
+            "recipient" => recipient,
+            "amount" => U256::from(3i64),
+        },
+    )
+    .build();
+
+    builder.exec(transfer_stake_request).expect_failure();
+
+    let event = event_dictionary(&builder, staking_contract_hash, 1);
+    let (event_name, fields) = String::from_bytes(&event).unwrap();
+    assert_eq!(event_name, "event_StakeTransferred");
+    let (_token_address, fields) = String::from_bytes(fields).unwrap();
+    let (staker_address, fields) = Key::from_bytes(fields).unwrap();
+    assert_eq!(staker_address, staker);
+    let (recipient_address, fields) = Key::from_bytes(fields).unwrap();
+    assert_eq!(recipient_address, recipient);
+    let (amount, _) = U256::from_bytes(fields).unwrap();
+    assert_eq!(amount, U256::from(3i64));
+}
+
+#[test]
+fn test_receipts_follow_the_stake() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let staker = Key::from(*DEFAULT_ACCOUNT_ADDR);
+    let recipient = Key::Account(AccountHash::new([4u8; 32]));
+    assert_eq!(
+        total_supply(&builder, staking_contract_hash),
+        U256::from(5i64)
+    );
+
+    let transfer_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "transfer",
+        runtime_args! {
+            "recipient" => recipient,
+            "amount" => U256::from(1i64),
+        },
+    )
+    .build();
+
+    builder.exec(transfer_request).expect_success().commit();
+
+    // The staker lets itself move 2 of its receipts on its behalf.
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staker,
+            "amount" => U256::from(2i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let transfer_from_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "transfer_from",
+        runtime_args! {
+            "owner" => staker,
+            "recipient" => recipient,
+            "amount" => U256::from(2i64),
+        },
+    )
+    .build();
+
+    builder
+        .exec(transfer_from_request)
+        .expect_success()
+        .commit();
+
+    // The allowance is spent.
+    let transfer_from_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "transfer_from",
+        runtime_args! {
+            "owner" => staker,
+            "recipient" => recipient,
+            "amount" => U256::from(1i64),
+        },
+    )
+    .build();
+
+    builder.exec(transfer_from_request).expect_failure();
+
+    let amount_staked = amount_staked_dictionary(&builder, staking_contract_hash, recipient);
+    assert_eq!(amount_staked, U256::from(3i64));
+    assert_eq!(
+        total_supply(&builder, staking_contract_hash),
+        U256::from(5i64)
+    );
+
+    // Withdrawing burns the receipts.
+    let withdraw_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "withdraw",
+        runtime_args! {
+            "amount" => U256::from(2i64),
+        },
+    )
+    .with_block_time(1781708875786u64)
+    .build();
+
+    builder.exec(withdraw_request).expect_success().commit();
+
+    let amount_staked = amount_staked_dictionary(&builder, staking_contract_hash, staker);
+    assert_eq!(amount_staked, U256::zero());
+    assert_eq!(
+        total_supply(&builder, staking_contract_hash),
+        U256::from(3i64)
+    );
+}
+
+#[test]
+fn test_allowlist_gates_stakes() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let staker = Key::from(*DEFAULT_ACCOUNT_ADDR);
+    let other = Key::Account(AccountHash::new([5u8; 32]));
+    let recipient = Key::Account(AccountHash::new([4u8; 32]));
+
+    let request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "set_allowlist_enabled",
+        runtime_args! {
+            "enabled" => true,
+        },
+    )
+    .build();
+
+    builder.exec(request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_failure();
+
+    // A tree of two leaves: the proof of one is the other.
+    let staker_leaf = create_blake2b_hash(staker.to_bytes().unwrap());
+    let other_leaf = create_blake2b_hash(other.to_bytes().unwrap());
+    let mut pair = Vec::new();
+    pair.extend_from_slice(&staker_leaf.min(other_leaf));
+    pair.extend_from_slice(&staker_leaf.max(other_leaf));
+    let merkle_root = create_blake2b_hash(pair);
+
+    let request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "set_merkle_root",
+        runtime_args! {
+            "merkle_root" => Some(merkle_root),
+        },
+    )
+    .build();
+
+    builder.exec(request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+            "merkle_proof" => vec![other_leaf],
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    // The proven staker joined the list and doesn't need the proof again.
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let amount_staked = amount_staked_dictionary(&builder, staking_contract_hash, staker);
+    assert_eq!(amount_staked, U256::from(10i64));
+
+    // Stakes can only move to allowlisted addresses.
+    let transfer_stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "transfer_stake",
+        runtime_args! {
+            "recipient" => recipient,
+            "amount" => U256::from(3i64),
+        },
+    )
+    .build();
+
+    builder.exec(transfer_stake_request).expect_failure();
+
+    let request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "add_to_allowlist",
+        runtime_args! {
+            "stakers" => vec![recipient, other],
+        },
+    )
+    .build();
+
+    builder.exec(request).expect_success().commit();
+
+    let transfer_stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "transfer_stake",
+        runtime_args! {
+            "recipient" => recipient,
+            "amount" => U256::from(3i64),
+        },
+    )
+    .build();
+
+    builder
+        .exec(transfer_stake_request)
+        .expect_success()
+        .commit();
+
+    let request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "remove_from_allowlist",
+        runtime_args! {
+            "stakers" => vec![recipient],
+        },
+    )
+    .build();
+
+    builder.exec(request).expect_success().commit();
+
+    let transfer_stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "transfer_stake",
+        runtime_args! {
+            "recipient" => recipient,
+            "amount" => U256::from(3i64),
+        },
+    )
+    .build();
+
+    builder.exec(transfer_stake_request).expect_failure();
+
+    let amount_staked = amount_staked_dictionary(&builder, staking_contract_hash, recipient);
+    assert_eq!(amount_staked, U256::from(3i64));
+}
+
+#[test]
+fn test_stake_limits_per_address() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+        "min_stake" => U256::from(2i64),
+        "max_stake_per_address" => U256::from(6i64),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    // Below the minimum stake.
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(1i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_failure();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    // Above the maximum stake per address.
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(2i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_failure();
+
+    let staker = Key::from(*DEFAULT_ACCOUNT_ADDR);
+    let amount_staked = amount_staked_dictionary(&builder, staking_contract_hash, staker);
+    assert_eq!(amount_staked, U256::from(5i64));
+
+    // The minimum can't go above the maximum.
+    let request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "set_stake_limits",
+        runtime_args! {
+            "min_stake" => U256::from(7i64),
+            "max_stake_per_address" => U256::from(6i64),
+        },
+    )
+    .build();
+
+    builder.exec(request).expect_failure();
+
+    let request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "set_stake_limits",
+        runtime_args! {
+            "min_stake" => U256::from(1i64),
+            "max_stake_per_address" => U256::zero(),
+        },
+    )
+    .build();
+
+    builder.exec(request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let amount_staked = amount_staked_dictionary(&builder, staking_contract_hash, staker);
+    assert_eq!(amount_staked, U256::from(10i64));
+}
+
+#[test]
+fn test_stakers_are_listed_once_in_stake_order() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let staker = Key::from(*DEFAULT_ACCOUNT_ADDR);
+    let beneficiary = Key::Account(AccountHash::new([3u8; 32]));
+    let recipient = Key::Account(AccountHash::new([4u8; 32]));
+
+    let stake_for_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake_for",
+        runtime_args! {
+            "beneficiary" => beneficiary,
+            "amount" => U256::from(3i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_for_request).expect_success().commit();
+
+    let transfer_stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "transfer_stake",
+        runtime_args! {
+            "recipient" => recipient,
+            "amount" => U256::from(2i64),
+        },
+    )
+    .build();
+
+    builder
+        .exec(transfer_stake_request)
+        .expect_success()
+        .commit();
+
+    // Staking again doesn't list the staker a second time.
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(2i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    assert_eq!(staker_count(&builder, staking_contract_hash), 3);
+    assert_eq!(staker_by_index(&builder, staking_contract_hash, 0), staker);
+    assert_eq!(
+        staker_by_index(&builder, staking_contract_hash, 1),
+        beneficiary
+    );
+    assert_eq!(
+        staker_by_index(&builder, staking_contract_hash, 2),
+        recipient
+    );
+}
+
+#[test]
+fn test_pool_and_staker_info_views() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let pool_info_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "pool_info",
+        runtime_args! {},
+    )
+    .build();
+
+    builder.exec(pool_info_request).expect_success().commit();
+
+    // Stakers and non stakers alike have a view.
+    for staker in [
+        Key::from(*DEFAULT_ACCOUNT_ADDR),
+        Key::Account(AccountHash::new([4u8; 32])),
+    ] {
+        let staker_info_request = ExecuteRequestBuilder::contract_call_by_hash(
+            *DEFAULT_ACCOUNT_ADDR,
+            staking_contract_hash,
+            "staker_info",
+            runtime_args! {
+                "staker" => staker,
+            },
+        )
+        .build();
+
+        builder.exec(staker_info_request).expect_success().commit();
+    }
+}
+
+#[test]
+fn test_reinstall_upgrades_the_package() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let staking_contract_package_hash = get_stacking_contract_package_hash(&builder);
+
+    // Installing again from the same account adds a version to the package.
+    let upgrade_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, STAKING_WASM, runtime_args! {})
+            .build();
+
+    builder.exec(upgrade_request).expect_success().commit();
+
+    let upgraded_contract_hash = get_stacking_contract_hash(&builder);
+    assert_ne!(upgraded_contract_hash, staking_contract_hash);
+    assert_eq!(
+        get_stacking_contract_package_hash(&builder),
+        staking_contract_package_hash
+    );
+
+    // The new version kept the state of the previous one.
+    let staker = Key::from(*DEFAULT_ACCOUNT_ADDR);
+    let amount_staked = amount_staked_dictionary(&builder, upgraded_contract_hash, staker);
+    assert_eq!(amount_staked, U256::from(5i64));
+    assert_eq!(
+        total_supply(&builder, upgraded_contract_hash),
+        U256::from(5i64)
+    );
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        upgraded_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let amount_staked = amount_staked_dictionary(&builder, upgraded_contract_hash, staker);
+    assert_eq!(amount_staked, U256::from(10i64));
+
+    // The previous version is disabled.
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(1i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_failure();
+}
+
+#[test]
+fn test_epochs_roll_stakes_over() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST).commit();
+
+    let erc20_runtime_args = runtime_args! {
+        "name" => "FERRUM_ERC20".to_string(),
+        "symbol" => "F_ERC20".to_string(),
+        "total_supply" => U256::from(500000i64),
+        "decimals" => 8u8,
+    };
+
+    let erc_20_install_request =
+        ExecuteRequestBuilder::standard(*DEFAULT_ACCOUNT_ADDR, ERC20_WASM, erc20_runtime_args)
+            .build();
+
+    builder
+        .exec(erc_20_install_request)
+        .expect_success()
+        .commit();
+
+    let erc20_contract_hash = get_erc20_contract_hash(&builder);
+    let erc20_contract_package_hash = get_erc20_contract_package_hash(&builder);
+
+    let staking_contract_runtime_args = runtime_args! {
+        "name" => "FerrumX".to_string(),
+        "address" => ADDRESS.to_string(),
+        "staking_starts" => 0u64,
+        "staking_ends" => 1781708875776u64,
+        "withdraw_starts" => 1781708875776u64,
+        "withdraw_ends" => 1781708875786u64,
+        "staking_total" => U256::from(500000i64),
+        "erc20_contract_package_hash" => Key::from(erc20_contract_package_hash),
+    };
+
+    let staking_contract_install_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        STAKING_WASM,
+        staking_contract_runtime_args,
+    )
+    .build();
+
+    builder
+        .exec(staking_contract_install_request)
+        .expect_success()
+        .commit();
+
+    let staking_contract_hash = get_stacking_contract_hash(&builder);
+    let staking_contract_key: Key = get_stacking_contract_package_hash(&builder).into();
+
+    let approve_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        erc20_contract_hash,
+        "approve",
+        runtime_args! {
+            "spender" => staking_contract_key,
+            "amount" => U256::from(10i64),
+        },
+    )
+    .build();
+
+    builder.exec(approve_request).expect_success().commit();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let add_reward_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "add_reward",
+        runtime_args! {
+            "reward_amount" => U256::from(4i64),
+            "withdrawable_amount" => U256::from(2i64),
+        },
+    )
+    .build();
+
+    builder.exec(add_reward_request).expect_success().commit();
+
+    let schedule_epoch_args = runtime_args! {
+        "staking_starts" => 1781708875786u64,
+        "staking_ends" => 1781708885786u64,
+        "withdraw_starts" => 1781708885786u64,
+        "withdraw_ends" => 1781708885796u64,
+        "staking_total" => U256::from(500000i64),
+    };
+
+    // The first epoch is still open.
+    let schedule_epoch_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "schedule_epoch",
+        schedule_epoch_args.clone(),
+    )
+    .build();
+
+    builder.exec(schedule_epoch_request).expect_failure();
+
+    let schedule_epoch_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "schedule_epoch",
+        schedule_epoch_args,
+    )
+    .with_block_time(1781708875786u64)
+    .build();
+
+    builder
+        .exec(schedule_epoch_request)
+        .expect_success()
+        .commit();
+
+    // The stake of the first epoch can't grow in the second one before it is rolled over.
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .with_block_time(1781708875790u64)
+    .build();
+
+    builder.exec(stake_request).expect_failure();
+
+    let roll_stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "roll_stake",
+        runtime_args! {},
+    )
+    .with_block_time(1781708875790u64)
+    .build();
+
+    builder.exec(roll_stake_request).expect_success().commit();
+
+    // The reward of the first epoch went out, the principal stayed.
+    let erc20_contract_key: Key = erc20_contract_hash.into();
+    let balance = balance_dictionary(&builder, erc20_contract_key, staking_contract_key);
+    assert_eq!(balance, U256::from(5i64));
+    let staker = Key::from(*DEFAULT_ACCOUNT_ADDR);
+    let amount_staked = amount_staked_dictionary(&builder, staking_contract_hash, staker);
+    assert_eq!(amount_staked, U256::from(5i64));
+
+    // The stake already belongs to the second epoch.
+    let roll_stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "roll_stake",
+        runtime_args! {},
+    )
+    .with_block_time(1781708875790u64)
+    .build();
+
+    builder.exec(roll_stake_request).expect_failure();
+
+    let stake_request = ExecuteRequestBuilder::contract_call_by_hash(
+        *DEFAULT_ACCOUNT_ADDR,
+        staking_contract_hash,
+        "stake",
+        runtime_args! {
+            "amount" => U256::from(5i64),
+        },
+    )
+    .with_block_time(1781708875790u64)
+    .build();
+
+    builder.exec(stake_request).expect_success().commit();
+
+    let amount_staked = amount_staked_dictionary(&builder, staking_contract_hash, staker);
+    assert_eq!(amount_staked, U256::from(10i64));
+}
+
 /// Creates a dictionary item key for an (owner, spender) pair.
 fn make_allowances_dictionary_item_key(owner: Key, spender: Key) -> String {
     let mut preimage = Vec::new();
@@ -1558,6 +4267,36 @@ pub fn get_stacking_contract_hash(builder: &WasmTestBuilder<InMemoryGlobalState>
     ContractHash::new(erc20_hash_addr)
 }
 
+pub fn get_contract_hash(
+    builder: &WasmTestBuilder<InMemoryGlobalState>,
+    name: &str,
+) -> ContractHash {
+    let hash_addr = builder
+        .get_expected_account(*DEFAULT_ACCOUNT_ADDR)
+        .named_keys()
+        .get(name)
+        .expect("must have this entry in named keys")
+        .into_hash()
+        .expect("must get hash_addr");
+
+    ContractHash::new(hash_addr)
+}
+
+pub fn get_contract_package_hash(
+    builder: &WasmTestBuilder<InMemoryGlobalState>,
+    name: &str,
+) -> ContractPackageHash {
+    let hash_addr = builder
+        .get_expected_account(*DEFAULT_ACCOUNT_ADDR)
+        .named_keys()
+        .get(name)
+        .expect("must have this entry in named keys")
+        .into_hash()
+        .expect("must get hash_addr");
+
+    ContractPackageHash::new(hash_addr)
+}
+
 pub(crate) fn get_erc20_contract_hash(
     builder: &WasmTestBuilder<InMemoryGlobalState>,
 ) -> ContractHash {
@@ -1617,6 +4356,171 @@ fn balance_dictionary(
     balance
 }
 
+fn events_length(
+    builder: &WasmTestBuilder<InMemoryGlobalState>,
+    staking_contract_hash: ContractHash,
+) -> u32 {
+    let events_length_uref = builder
+        .query(None, Key::from(staking_contract_hash), &[])
+        .unwrap()
+        .as_contract()
+        .expect("must have staking contract")
+        .named_keys()
+        .get("__events_length")
+        .expect("must have __events_length entry")
+        .as_uref()
+        .expect("must be a uref")
+        .to_owned();
+
+    builder
+        .query(None, Key::from(events_length_uref), &[])
+        .expect("should be stored value.")
+        .as_cl_value()
+        .expect("should be cl value.")
+        .clone()
+        .into_t()
+        .expect("must convert to u32")
+}
+
+fn total_supply(
+    builder: &WasmTestBuilder<InMemoryGlobalState>,
+    staking_contract_hash: ContractHash,
+) -> U256 {
+    let total_supply_uref = builder
+        .query(None, Key::from(staking_contract_hash), &[])
+        .unwrap()
+        .as_contract()
+        .expect("must have staking contract")
+        .named_keys()
+        .get("total_supply")
+        .expect("must have total_supply entry")
+        .as_uref()
+        .expect("must be a uref")
+        .to_owned();
+
+    builder
+        .query(None, Key::from(total_supply_uref), &[])
+        .expect("should be stored value.")
+        .as_cl_value()
+        .expect("should be cl value.")
+        .clone()
+        .into_t()
+        .expect("must convert to U256")
+}
+
+fn event_dictionary(
+    builder: &WasmTestBuilder<InMemoryGlobalState>,
+    staking_contract_hash: ContractHash,
+    index: u32,
+) -> Bytes {
+    let events_seed_uref = builder
+        .query(None, Key::from(staking_contract_hash), &[])
+        .unwrap()
+        .as_contract()
+        .expect("must have staking contract")
+        .named_keys()
+        .get("__events")
+        .expect("must have __events entry")
+        .as_uref()
+        .expect("must be a uref")
+        .to_owned();
+
+    builder
+        .query_dictionary_item(None, events_seed_uref, &index.to_string())
+        .expect("should be stored value.")
+        .as_cl_value()
+        .expect("should be cl value.")
+        .clone()
+        .into_t()
+        .expect("must convert to Bytes")
+}
+
+fn staker_count(
+    builder: &WasmTestBuilder<InMemoryGlobalState>,
+    staking_contract_hash: ContractHash,
+) -> u64 {
+    let staker_count_uref = builder
+        .query(None, Key::from(staking_contract_hash), &[])
+        .unwrap()
+        .as_contract()
+        .expect("must have staking contract")
+        .named_keys()
+        .get("staker_count")
+        .expect("must have staker_count entry")
+        .as_uref()
+        .expect("must be a uref")
+        .to_owned();
+
+    builder
+        .query(None, Key::from(staker_count_uref), &[])
+        .expect("should be stored value.")
+        .as_cl_value()
+        .expect("should be cl value.")
+        .clone()
+        .into_t()
+        .expect("must convert to u64")
+}
+
+fn staker_by_index(
+    builder: &WasmTestBuilder<InMemoryGlobalState>,
+    staking_contract_hash: ContractHash,
+    index: u64,
+) -> Key {
+    let stakers_seed_uref = builder
+        .query(None, Key::from(staking_contract_hash), &[])
+        .unwrap()
+        .as_contract()
+        .expect("must have staking contract")
+        .named_keys()
+        .get("stakers_by_index")
+        .expect("must have stakers_by_index entry")
+        .as_uref()
+        .expect("must be a uref")
+        .to_owned();
+
+    builder
+        .query_dictionary_item(None, stakers_seed_uref, &index.to_string())
+        .expect("should be stored value.")
+        .as_cl_value()
+        .expect("should be cl value.")
+        .clone()
+        .into_t()
+        .expect("must convert to Key")
+}
+
+fn amount_staked_dictionary(
+    builder: &WasmTestBuilder<InMemoryGlobalState>,
+    staking_contract_hash: ContractHash,
+    staker: Key,
+) -> U256 {
+    let amount_staked_seed_uref = builder
+        .query(None, Key::from(staking_contract_hash), &[])
+        .unwrap()
+        .as_contract()
+        .expect("must have staking contract")
+        .named_keys()
+        .get("amount_staked_by_addresses_dict")
+        .expect("must have amount_staked_by_addresses_dict entry")
+        .as_uref()
+        .expect("must be a uref")
+        .to_owned();
+
+    let dict_item_key = match staker {
+        Key::Account(account_hash) => account_hash.to_string(),
+        Key::Hash(hash) => hex::encode(hash),
+        _ => panic!("unexpected key variant"),
+    };
+
+    builder
+        .query_dictionary_item(None, amount_staked_seed_uref, &dict_item_key)
+        .expect("should be stored value.")
+        .as_cl_value()
+        .expect("should be cl value.")
+        .clone()
+        .into_t()
+        .expect("must convert to U256")
+}
+
 fn allowance_dictionary(
     builder: &WasmTestBuilder<InMemoryGlobalState>,
     erc20_contract_key: Key,
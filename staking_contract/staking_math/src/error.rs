@@ -0,0 +1,12 @@
+// This code defines an enum for the errors that can occur in the reward math.
+
+/// An enum for the errors that can occur in the reward math.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathError {
+    /// An amount does not fit in 256 bits.
+    Overflow,
+    /// A subtraction goes below zero.
+    CheckedSub,
+    /// A reward formula divides by zero.
+    DivisionByZero,
+}
@@ -0,0 +1,10 @@
+//! Reward math of the staking contract and the staking factory.
+//!
+//! Nothing here touches the runtime: the pool state and the block time come in as plain values,
+//! so the formulas can be tested on the host.
+#![no_std]
+
+pub mod error;
+pub mod math;
+pub mod pool;
+pub mod reward;
@@ -0,0 +1,44 @@
+//! Checked arithmetic for the reward formulas.
+//!
+//! Every operation returns a `MathError` instead of trapping on overflow or on a zero denominator,
+//! and every division states which way it rounds, so that the formulas can round in the pool's
+//! favour.
+use crate::error::MathError;
+use casper_types::U256;
+
+/// Returns `a + b`.
+pub fn add(a: U256, b: U256) -> Result<U256, MathError> {
+    a.checked_add(b).ok_or(MathError::Overflow)
+}
+
+/// Returns `a - b`.
+pub fn sub(a: U256, b: U256) -> Result<U256, MathError> {
+    a.checked_sub(b).ok_or(MathError::CheckedSub)
+}
+
+/// Returns `a * b`.
+pub fn mul(a: U256, b: U256) -> Result<U256, MathError> {
+    a.checked_mul(b).ok_or(MathError::Overflow)
+}
+
+/// Returns `a * b / denominator`, rounded down.
+pub fn mul_div_down(a: U256, b: U256, denominator: U256) -> Result<U256, MathError> {
+    if denominator.is_zero() {
+        return Err(MathError::DivisionByZero);
+    }
+    Ok(mul(a, b)? / denominator)
+}
+
+/// Returns `a * b / denominator`, rounded up.
+pub fn mul_div_up(a: U256, b: U256, denominator: U256) -> Result<U256, MathError> {
+    if denominator.is_zero() {
+        return Err(MathError::DivisionByZero);
+    }
+    let product = mul(a, b)?;
+    let quotient = product / denominator;
+    if (product % denominator).is_zero() {
+        Ok(quotient)
+    } else {
+        add(quotient, U256::one())
+    }
+}
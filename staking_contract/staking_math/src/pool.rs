@@ -0,0 +1,161 @@
+//! Payouts of a pool, computed from a snapshot of its state and the block time.
+//!
+//! The caller reads the state out of storage, asks for the payout as of `now` and writes back
+//! whatever changed, so every time-dependent decision is made here.
+use crate::error::MathError;
+use crate::math::sub;
+use crate::reward;
+use casper_types::U256;
+
+/// State of a pool with fixed staking and withdraw windows, e.g. an epoch of the staking contract
+/// or a pool of the staking factory.
+///
+/// Pools without reward multipliers pass the staked amounts as weights.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FixedWindowPool {
+    /// End of the staking window, in milliseconds.
+    pub staking_ends: u64,
+    /// End of the withdraw window, in milliseconds.
+    pub withdraw_ends: u64,
+    /// Reward shared among the stakes withdrawn before the withdraw window closes.
+    pub early_withdraw_reward: U256,
+    /// Reward left for the stakes that haven't been withdrawn.
+    pub reward_balance: U256,
+    /// Weight of all the stakes made in the staking window.
+    pub weighted_total: U256,
+    /// Weight of the stakes that haven't been withdrawn.
+    pub weighted_balance: U256,
+}
+
+/// Payout of a stake withdrawn before the withdraw window closes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EarlyWithdrawal {
+    /// Reward the stake earned, which leaves the reward balance.
+    pub accrued: U256,
+    /// Part of the earned reward that hasn't been claimed beforehand, and is paid now.
+    pub reward: U256,
+    /// Part of the stake taken by the early withdraw penalty.
+    pub penalty: U256,
+    /// Part of the stake paid back.
+    pub paid_amount: U256,
+}
+
+impl FixedWindowPool {
+    /// Returns whether the withdraw window has closed at `now`.
+    pub fn is_closed(&self, now: u64) -> bool {
+        now >= self.withdraw_ends
+    }
+
+    /// Returns the reward for withdrawing a stake of the given weight at `now`, before the
+    /// withdraw window closes.
+    pub fn early_withdraw_reward(&self, weight: U256, now: u64) -> Result<U256, MathError> {
+        reward::early_withdraw(
+            now,
+            self.staking_ends,
+            self.withdraw_ends,
+            self.early_withdraw_reward,
+            self.weighted_total,
+            weight,
+        )
+    }
+
+    /// Returns the reward for withdrawing a stake of the given weight after the withdraw window
+    /// closed.
+    pub fn after_close_reward(&self, weight: U256) -> Result<U256, MathError> {
+        reward::after_close(self.reward_balance, self.weighted_balance, weight)
+    }
+
+    /// Returns the reward that a stake of the given weight has earned at `now`, whether it was
+    /// claimed or not.
+    pub fn accrued_reward(&self, weight: U256, now: u64) -> Result<U256, MathError> {
+        if weight.is_zero() || now < self.staking_ends {
+            Ok(U256::zero())
+        } else if !self.is_closed(now) {
+            self.early_withdraw_reward(weight, now)
+        } else {
+            self.after_close_reward(weight)
+        }
+    }
+
+    /// Returns the payout of withdrawing `amount` tokens of the given weight at `now`, before the
+    /// withdraw window closes.
+    ///
+    /// `claimed` is the part of the reward claimed beforehand, which is not paid again, and
+    /// `penalty` the early withdraw penalty in basis points.
+    pub fn early_withdrawal(
+        &self,
+        amount: U256,
+        weight: U256,
+        claimed: U256,
+        penalty: u32,
+        now: u64,
+    ) -> Result<EarlyWithdrawal, MathError> {
+        let accrued = self.early_withdraw_reward(weight, now)?;
+        let penalty = reward::penalty(amount, penalty)?;
+        Ok(EarlyWithdrawal {
+            accrued,
+            reward: accrued.saturating_sub(claimed),
+            penalty,
+            paid_amount: sub(amount, penalty)?,
+        })
+    }
+}
+
+/// State of a pool that streams its reward at a fixed rate.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StreamingPool {
+    /// Reward accumulated per staked token, scaled by `reward::ACC_REWARD_PRECISION`.
+    pub acc_reward_per_share: U256,
+    /// Block time of the last update, in milliseconds.
+    pub last_reward_time: u64,
+    /// Reward streamed per second.
+    pub reward_rate: U256,
+    /// Reward that has not been streamed yet.
+    pub reward_balance: U256,
+    /// Weight of the stakes that haven't been withdrawn.
+    pub weighted_balance: U256,
+}
+
+impl StreamingPool {
+    /// Returns the accumulated reward per staked token as of `now`, together with the reward
+    /// streamed since the last update.
+    ///
+    /// The streamed reward never exceeds the reward balance.
+    pub fn accrued_reward_per_share(&self, now: u64) -> Result<(U256, U256), MathError> {
+        if now <= self.last_reward_time || self.weighted_balance.is_zero() {
+            return Ok((self.acc_reward_per_share, U256::zero()));
+        }
+        let streamed =
+            reward::emitted(self.reward_rate, self.last_reward_time, now)?.min(self.reward_balance);
+        Ok((
+            reward::accumulate(self.acc_reward_per_share, streamed, self.weighted_balance)?,
+            streamed,
+        ))
+    }
+
+    /// Returns the pool brought up to `now`.
+    pub fn update(&self, now: u64) -> Result<StreamingPool, MathError> {
+        if now <= self.last_reward_time {
+            return Ok(*self);
+        }
+        let (acc_reward_per_share, streamed) = self.accrued_reward_per_share(now)?;
+        Ok(StreamingPool {
+            acc_reward_per_share,
+            last_reward_time: now,
+            reward_balance: sub(self.reward_balance, streamed)?,
+            ..*self
+        })
+    }
+
+    /// Returns the reward that a stake of the given weight has earned at `now` since its
+    /// `reward_debt` was recorded.
+    pub fn pending_reward(
+        &self,
+        weight: U256,
+        reward_debt: U256,
+        now: u64,
+    ) -> Result<U256, MathError> {
+        let (acc_reward_per_share, _) = self.accrued_reward_per_share(now)?;
+        reward::pending(weight, acc_reward_per_share, reward_debt)
+    }
+}
@@ -0,0 +1,123 @@
+//! Reward formulas shared by the staking contract and the pools of the staking factory.
+//!
+//! Amounts paid out by the pool round down and amounts kept by the pool round up, so rounding
+//! never lets the pool pay out more than it holds.
+use crate::error::MathError;
+use crate::math::{add, mul, mul_div_down, mul_div_up};
+use casper_types::U256;
+
+/// Returns the reward for withdrawing `amount` before the withdraw window closes.
+///
+/// The early withdraw reward is shared pro rata to `amount / staked_total`, scaled by the part
+/// of the withdraw window that has already passed.
+pub fn early_withdraw(
+    now: u64,
+    staking_ends: u64,
+    withdraw_ends: u64,
+    early_withdraw_reward: U256,
+    staked_total: U256,
+    amount: U256,
+) -> Result<U256, MathError> {
+    let window = withdraw_ends
+        .checked_sub(staking_ends)
+        .ok_or(MathError::CheckedSub)?;
+    let elapsed = now.checked_sub(staking_ends).ok_or(MathError::CheckedSub)?;
+    mul_div_down(
+        mul(U256::from(elapsed), early_withdraw_reward)?,
+        amount,
+        mul(U256::from(window), staked_total)?,
+    )
+}
+
+/// Returns the reward for withdrawing `amount` after the withdraw window closed.
+///
+/// What is left of the reward balance is shared pro rata to `amount / staked_balance`.
+pub fn after_close(
+    reward_balance: U256,
+    staked_balance: U256,
+    amount: U256,
+) -> Result<U256, MathError> {
+    mul_div_down(reward_balance, amount, staked_balance)
+}
+
+/// Returns the part of `value`, held for a stake of `staked` tokens, that goes with `amount` of
+/// them, e.g. the part of the weight that leaves with a withdrawal, rounded down.
+pub fn pro_rata(value: U256, amount: U256, staked: U256) -> Result<U256, MathError> {
+    if staked.is_zero() {
+        return Ok(U256::zero());
+    }
+    mul_div_down(value, amount, staked)
+}
+
+/// Returns the part of `value` that goes with `amount` like `pro_rata`, rounded up, e.g. the part
+/// of the claimed reward that is not paid again on withdraw.
+pub fn pro_rata_up(value: U256, amount: U256, staked: U256) -> Result<U256, MathError> {
+    if staked.is_zero() {
+        return Ok(U256::zero());
+    }
+    mul_div_up(value, amount, staked)
+}
+
+/// Reward multiplier, in basis points, of a stake that isn't locked.
+pub const MULTIPLIER_BASE: u32 = 10_000;
+
+/// Returns the weight `amount` carries in the reward formulas under `multiplier` basis points.
+pub fn weight(amount: U256, multiplier: u32) -> Result<U256, MathError> {
+    mul_div_down(amount, U256::from(multiplier), U256::from(MULTIPLIER_BASE))
+}
+
+/// Returns the part of `amount` taken by a penalty of `penalty` basis points.
+pub fn penalty(amount: U256, penalty: u32) -> Result<U256, MathError> {
+    mul_div_up(amount, U256::from(penalty), U256::from(MULTIPLIER_BASE))
+}
+
+/// Scale of the accumulated reward per staked token in streaming mode.
+pub const ACC_REWARD_PRECISION: u64 = 1_000_000_000_000;
+
+/// Returns the reward streamed at `reward_rate` tokens per second between two block times, given
+/// in milliseconds.
+pub fn emitted(reward_rate: U256, from: u64, to: u64) -> Result<U256, MathError> {
+    mul_div_down(
+        reward_rate,
+        U256::from(to.saturating_sub(from)),
+        U256::from(1000u64),
+    )
+}
+
+/// Returns the accumulated reward per staked token once `reward` is shared among `staked_balance`.
+pub fn accumulate(
+    acc_reward_per_share: U256,
+    reward: U256,
+    staked_balance: U256,
+) -> Result<U256, MathError> {
+    if staked_balance.is_zero() {
+        return Ok(acc_reward_per_share);
+    }
+    add(
+        acc_reward_per_share,
+        mul_div_down(reward, U256::from(ACC_REWARD_PRECISION), staked_balance)?,
+    )
+}
+
+/// Returns the reward already accounted for `amount` at the given accumulated reward per token.
+pub fn reward_debt(amount: U256, acc_reward_per_share: U256) -> Result<U256, MathError> {
+    mul_div_up(
+        amount,
+        acc_reward_per_share,
+        U256::from(ACC_REWARD_PRECISION),
+    )
+}
+
+/// Returns the reward `amount` earned since its `reward_debt` was recorded.
+pub fn pending(
+    amount: U256,
+    acc_reward_per_share: U256,
+    reward_debt: U256,
+) -> Result<U256, MathError> {
+    let accrued = mul_div_down(
+        amount,
+        acc_reward_per_share,
+        U256::from(ACC_REWARD_PRECISION),
+    )?;
+    Ok(accrued.saturating_sub(reward_debt))
+}
@@ -0,0 +1,110 @@
+use casper_types::U256;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use staking_math::pool::{EarlyWithdrawal, FixedWindowPool, StreamingPool};
+
+fn fixed_window_pool() -> FixedWindowPool {
+    FixedWindowPool {
+        staking_ends: 1_000,
+        withdraw_ends: 2_000,
+        early_withdraw_reward: U256::from(100u64),
+        reward_balance: U256::from(1_000u64),
+        weighted_total: U256::from(400u64),
+        weighted_balance: U256::from(400u64),
+    }
+}
+
+#[test]
+fn test_fixed_window_pool_pays_by_window() {
+    let pool = fixed_window_pool();
+    let weight = U256::from(100u64);
+    // Nothing is earned while staking is open.
+    assert_eq!(pool.accrued_reward(weight, 500), Ok(U256::zero()));
+    // Half-way through the withdraw window, a quarter of the stakes earns an eighth.
+    assert_eq!(pool.accrued_reward(weight, 1_500), Ok(U256::from(12u64)));
+    assert!(!pool.is_closed(1_999));
+    // Once it closes, the stake takes its share of the reward balance.
+    assert!(pool.is_closed(2_000));
+    assert_eq!(pool.accrued_reward(weight, 2_000), Ok(U256::from(250u64)));
+    assert_eq!(pool.accrued_reward(U256::zero(), 2_000), Ok(U256::zero()));
+}
+
+#[test]
+fn test_early_withdrawal_takes_penalty_and_claimed_reward() {
+    let pool = fixed_window_pool();
+    let withdrawal = pool.early_withdrawal(
+        U256::from(100u64),
+        U256::from(200u64),
+        U256::from(5u64),
+        1_000,
+        1_500,
+    );
+    assert_eq!(
+        withdrawal,
+        Ok(EarlyWithdrawal {
+            accrued: U256::from(25u64),
+            reward: U256::from(20u64),
+            penalty: U256::from(10u64),
+            paid_amount: U256::from(90u64),
+        })
+    );
+}
+
+#[test]
+fn test_streaming_pool_update() {
+    let pool = StreamingPool {
+        acc_reward_per_share: U256::zero(),
+        last_reward_time: 1_000,
+        reward_rate: U256::from(10u64),
+        reward_balance: U256::from(25u64),
+        weighted_balance: U256::from(5u64),
+    };
+    // Nothing streams before the last update.
+    assert_eq!(pool.update(1_000), Ok(pool));
+    // Two seconds stream 20 tokens, 4 per staked token.
+    let updated = pool.update(3_000).unwrap();
+    assert_eq!(updated.last_reward_time, 3_000);
+    assert_eq!(updated.reward_balance, U256::from(5u64));
+    assert_eq!(
+        pool.pending_reward(U256::from(5u64), U256::zero(), 3_000),
+        Ok(U256::from(20u64))
+    );
+    // The stream stops once the reward balance runs dry.
+    let updated = updated.update(10_000).unwrap();
+    assert!(updated.reward_balance.is_zero());
+    assert_eq!(
+        pool.pending_reward(U256::from(5u64), U256::zero(), 10_000),
+        Ok(U256::from(25u64))
+    );
+}
+
+proptest! {
+    // However the updates are spaced, the stakes never earn more than has streamed.
+    #[test]
+    fn test_streaming_pool_never_pays_more_than_streamed(
+        reward_rate in 0u64..1_000_000,
+        reward_balance in 0u64..1_000_000_000,
+        weights in vec(1u64..1_000_000, 1..10),
+        steps in vec(0u64..100_000, 1..10),
+    ) {
+        let weighted_balance: u64 = weights.iter().sum();
+        let mut pool = StreamingPool {
+            reward_rate: U256::from(reward_rate),
+            reward_balance: U256::from(reward_balance),
+            weighted_balance: U256::from(weighted_balance),
+            ..StreamingPool::default()
+        };
+        for step in steps {
+            let now = pool.last_reward_time + step;
+            pool = pool.update(now).unwrap();
+        }
+        let streamed = U256::from(reward_balance) - pool.reward_balance;
+        let mut paid = U256::zero();
+        for weight in weights {
+            paid += pool
+                .pending_reward(U256::from(weight), U256::zero(), pool.last_reward_time)
+                .unwrap();
+        }
+        prop_assert!(paid <= streamed);
+    }
+}
@@ -0,0 +1,131 @@
+use casper_types::U256;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use staking_math::error::MathError;
+use staking_math::math::{self, mul_div_down, mul_div_up};
+use staking_math::reward::{self, ACC_REWARD_PRECISION, MULTIPLIER_BASE};
+
+#[test]
+fn test_math_reports_overflow_and_division_by_zero() {
+    assert!(matches!(
+        math::add(U256::MAX, U256::one()),
+        Err(MathError::Overflow)
+    ));
+    assert!(matches!(
+        math::mul(U256::MAX, U256::from(2u64)),
+        Err(MathError::Overflow)
+    ));
+    assert!(matches!(
+        math::sub(U256::zero(), U256::one()),
+        Err(MathError::CheckedSub)
+    ));
+    assert!(matches!(
+        mul_div_down(U256::one(), U256::one(), U256::zero()),
+        Err(MathError::DivisionByZero)
+    ));
+    assert!(matches!(
+        mul_div_up(U256::one(), U256::one(), U256::zero()),
+        Err(MathError::DivisionByZero)
+    ));
+    // Nobody is staked once the reward is shared out.
+    assert!(matches!(
+        reward::after_close(U256::from(4u64), U256::zero(), U256::from(5u64)),
+        Err(MathError::DivisionByZero)
+    ));
+    // The withdraw window is empty.
+    assert!(matches!(
+        reward::early_withdraw(
+            10,
+            10,
+            10,
+            U256::from(4u64),
+            U256::from(5u64),
+            U256::from(5u64)
+        ),
+        Err(MathError::DivisionByZero)
+    ));
+}
+
+proptest! {
+    #[test]
+    fn test_mul_div_rounds_down_and_up(a in any::<u64>(), b in any::<u64>(), denominator in 1..u64::MAX) {
+        let product = u128::from(a) * u128::from(b);
+        let quotient = product / u128::from(denominator);
+        let rounded_up = quotient + u128::from(product % u128::from(denominator) != 0);
+
+        let down = mul_div_down(U256::from(a), U256::from(b), U256::from(denominator)).unwrap();
+        let up = mul_div_up(U256::from(a), U256::from(b), U256::from(denominator)).unwrap();
+        prop_assert_eq!(down, U256::from(quotient));
+        prop_assert_eq!(up, U256::from(rounded_up));
+    }
+
+    #[test]
+    fn test_after_close_never_pays_more_than_the_reward_balance(
+        stakes in vec(1..u64::MAX / 64, 1..32),
+        reward_balance in any::<u64>(),
+    ) {
+        let staked_balance = stakes.iter().fold(U256::zero(), |total, stake| total + *stake);
+        let paid = stakes.iter().fold(U256::zero(), |paid, stake| {
+            paid + reward::after_close(U256::from(reward_balance), staked_balance, U256::from(*stake))
+                .unwrap()
+        });
+        prop_assert!(paid <= U256::from(reward_balance));
+    }
+
+    #[test]
+    fn test_early_withdraw_never_pays_more_than_the_early_withdraw_reward(
+        staking_ends in 0..u64::MAX / 2,
+        window in 1..u64::MAX / 2,
+        elapsed in any::<u64>(),
+        early_withdraw_reward in any::<u64>(),
+        staked_total in 1..u64::MAX,
+        amount in any::<u64>(),
+    ) {
+        let elapsed = elapsed % window;
+        let amount = amount % staked_total;
+        let reward = reward::early_withdraw(
+            staking_ends + elapsed,
+            staking_ends,
+            staking_ends + window,
+            U256::from(early_withdraw_reward),
+            U256::from(staked_total),
+            U256::from(amount),
+        )
+        .unwrap();
+        prop_assert!(reward <= U256::from(early_withdraw_reward));
+    }
+
+    #[test]
+    fn test_penalty_rounds_in_the_pools_favour(amount in any::<u64>(), penalty in 0..MULTIPLIER_BASE) {
+        let taken = reward::penalty(U256::from(amount), penalty).unwrap();
+        prop_assert!(taken <= U256::from(amount));
+        prop_assert!(taken * U256::from(MULTIPLIER_BASE) >= U256::from(amount) * U256::from(penalty));
+    }
+
+    #[test]
+    fn test_pro_rata_stays_within_the_value(value in any::<u64>(), staked in 1..u64::MAX, amount in any::<u64>()) {
+        let amount = U256::from(amount % staked + 1).min(U256::from(staked));
+        let down = reward::pro_rata(U256::from(value), amount, U256::from(staked)).unwrap();
+        let up = reward::pro_rata_up(U256::from(value), amount, U256::from(staked)).unwrap();
+        prop_assert!(down <= up);
+        prop_assert!(up <= U256::from(value));
+    }
+
+    #[test]
+    fn test_streamed_reward_never_exceeds_the_accumulated_share(
+        weight in any::<u64>(),
+        acc_before in any::<u64>(),
+        increase in any::<u64>(),
+    ) {
+        let acc_before = U256::from(acc_before);
+        let acc_after = acc_before + increase;
+        let reward_debt = reward::reward_debt(U256::from(weight), acc_before).unwrap();
+        prop_assert_eq!(
+            reward::pending(U256::from(weight), acc_before, reward_debt).unwrap(),
+            U256::zero()
+        );
+        let pending = reward::pending(U256::from(weight), acc_after, reward_debt).unwrap();
+        let exact = U256::from(weight) * increase / U256::from(ACC_REWARD_PRECISION);
+        prop_assert!(pending <= exact);
+    }
+}
@@ -1,4 +1,3 @@
-use casper_contract::contract_api::runtime;
 use casper_types::{ApiError, Key};
 
 use crate::{ContractContext, ContractStorage, Dict};
@@ -6,8 +5,11 @@ use crate::{ContractContext, ContractStorage, Dict};
 const ADMINS_DICT: &str = "admins";
 
 pub trait AdminControl<Storage: ContractStorage>: ContractContext<Storage> {
+    /// The error a caller that is not an admin is reverted with.
+    const NOT_AN_ADMIN: ApiError = ApiError::PermissionDenied;
+
     fn init(&mut self) {
-        Admins::init();
+        Admins::init(self.storage());
     }
 
     fn add_admin(&mut self, address: Key) {
@@ -17,37 +19,37 @@ pub trait AdminControl<Storage: ContractStorage>: ContractContext<Storage> {
 
     fn disable_admin(&mut self, address: Key) {
         self.assert_caller_is_admin();
-        Admins::instance().disable_admin(&address);
+        Admins::instance(self.storage()).disable_admin(&address);
     }
 
     fn add_admin_without_checked(&mut self, address: Key) {
-        Admins::instance().add_admin(&address);
+        Admins::instance(self.storage()).add_admin(&address);
     }
 
     fn assert_caller_is_admin(&self) {
         let caller = self.get_caller();
         if !self.is_admin(caller) {
-            runtime::revert(ApiError::User(20));
+            self.storage().revert(Self::NOT_AN_ADMIN);
         }
     }
 
     fn is_admin(&self, address: Key) -> bool {
-        Admins::instance().is_admin(&address)
+        Admins::instance(self.storage()).is_admin(&address)
     }
 }
 
-struct Admins {
-    dict: Dict,
+struct Admins<'a, S: ContractStorage> {
+    dict: Dict<'a, S>,
 }
 
-impl Admins {
-    pub fn instance() -> Admins {
+impl<'a, S: ContractStorage> Admins<'a, S> {
+    pub fn instance(storage: &'a S) -> Admins<'a, S> {
         Admins {
-            dict: Dict::instance(ADMINS_DICT),
+            dict: Dict::instance(storage, ADMINS_DICT),
         }
     }
-    pub fn init() {
-        Dict::init(ADMINS_DICT);
+    pub fn init(storage: &S) {
+        Dict::init(storage, ADMINS_DICT);
     }
 
     pub fn is_admin(&self, key: &Key) -> bool {
@@ -1,7 +1,6 @@
-use casper_contract::unwrap_or_revert::UnwrapOrRevert;
 use casper_types::{system::CallStackElement, Key};
 
-use crate::ContractStorage;
+use crate::{ContractStorage, UnwrapOrRevertIn};
 
 pub trait ContractContext<Storage: ContractStorage> {
     fn storage(&self) -> &Storage;
@@ -9,12 +8,12 @@ pub trait ContractContext<Storage: ContractStorage> {
     fn get_caller(&self) -> Key {
         let call_stack = self.storage().call_stack();
         let caller = call_stack.get(call_stack.len() - 2);
-        element_to_key(caller.unwrap_or_revert())
+        element_to_key(caller.unwrap_or_revert_in(self.storage()))
     }
 
     fn self_addr(&self) -> Key {
         let call_stack = self.storage().call_stack();
-        element_to_key(call_stack.last().unwrap_or_revert())
+        element_to_key(call_stack.last().unwrap_or_revert_in(self.storage()))
     }
 }
 
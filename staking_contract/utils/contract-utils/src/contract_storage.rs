@@ -1,11 +1,118 @@
 use alloc::vec::Vec;
 use core::cell::OnceCell;
 
-use casper_contract::contract_api::runtime;
-use casper_types::system::CallStackElement;
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    bytesrepr::{FromBytes, ToBytes},
+    system::CallStackElement,
+    ApiError, CLTyped, ContractPackageHash, Key, RuntimeArgs, URef,
+};
 
+/// Everything a contract reads from or writes to the host: its call stack, named keys, stored
+/// values and dictionaries, the block time, hashing, calls to other contracts and reverts.
+///
+/// Values that cannot be read as the requested type revert, like they do on chain.
 pub trait ContractStorage {
+    /// Returns the call stack of the current execution.
     fn call_stack(&self) -> &[CallStackElement];
+
+    /// Returns the key stored under `name` in the named keys of the contract.
+    fn get_named_key(&self, name: &str) -> Option<Key>;
+
+    /// Stores `key` under `name` in the named keys of the contract.
+    fn put_named_key(&self, name: &str, key: Key);
+
+    /// Removes `name` from the named keys of the contract.
+    fn remove_named_key(&self, name: &str);
+
+    /// Stores `value` under a new [`URef`].
+    fn new_uref<T: CLTyped + ToBytes>(&self, value: T) -> URef;
+
+    /// Reads the value stored under `uref`.
+    fn read<T: CLTyped + FromBytes>(&self, uref: URef) -> Option<T>;
+
+    /// Overwrites the value stored under `uref`.
+    fn write<T: CLTyped + ToBytes>(&self, uref: URef, value: T);
+
+    /// Creates a dictionary stored under `name` in the named keys of the contract.
+    fn new_dictionary(&self, name: &str) -> URef;
+
+    /// Reads the value stored under `key` in a dictionary.
+    fn dictionary_get<T: CLTyped + FromBytes>(&self, dictionary: URef, key: &str) -> Option<T>;
+
+    /// Stores `value` under `key` in a dictionary.
+    fn dictionary_put<T: CLTyped + ToBytes>(&self, dictionary: URef, key: &str, value: T);
+
+    /// Returns the block time, in milliseconds.
+    fn block_time(&self) -> u64;
+
+    /// Returns the blake2b hash of `input`.
+    fn blake2b(&self, input: &[u8]) -> [u8; 32];
+
+    /// Calls an entry point of the latest version of a contract and returns its result.
+    fn call_versioned_contract<T: CLTyped + FromBytes>(
+        &self,
+        contract_package_hash: ContractPackageHash,
+        entry_point: &str,
+        args: RuntimeArgs,
+    ) -> T;
+
+    /// Stops the execution with the given error.
+    fn revert(&self, error: ApiError) -> !;
+}
+
+/// Like [`UnwrapOrRevert`], but reverts through a [`ContractStorage`], so the code using it also
+/// runs off chain.
+pub trait UnwrapOrRevertIn<T> {
+    /// Unwraps the value, or reverts with a default error.
+    fn unwrap_or_revert_in<S: ContractStorage>(self, storage: &S) -> T;
+
+    /// Unwraps the value, or reverts with the given error.
+    fn unwrap_or_revert_with_in<S: ContractStorage, E: Into<ApiError>>(
+        self,
+        storage: &S,
+        error: E,
+    ) -> T;
+}
+
+impl<T> UnwrapOrRevertIn<T> for Option<T> {
+    fn unwrap_or_revert_in<S: ContractStorage>(self, storage: &S) -> T {
+        self.unwrap_or_revert_with_in(storage, ApiError::None)
+    }
+
+    fn unwrap_or_revert_with_in<S: ContractStorage, E: Into<ApiError>>(
+        self,
+        storage: &S,
+        error: E,
+    ) -> T {
+        match self {
+            Some(value) => value,
+            None => storage.revert(error.into()),
+        }
+    }
+}
+
+impl<T, F: Into<ApiError>> UnwrapOrRevertIn<T> for Result<T, F> {
+    fn unwrap_or_revert_in<S: ContractStorage>(self, storage: &S) -> T {
+        match self {
+            Ok(value) => value,
+            Err(error) => storage.revert(error.into()),
+        }
+    }
+
+    fn unwrap_or_revert_with_in<S: ContractStorage, E: Into<ApiError>>(
+        self,
+        storage: &S,
+        error: E,
+    ) -> T {
+        match self {
+            Ok(value) => value,
+            Err(_) => storage.revert(error.into()),
+        }
+    }
 }
 
 #[derive(Default)]
@@ -18,4 +125,61 @@ impl ContractStorage for OnChainContractStorage {
         let call_stack = self.call_stack.get_or_init(runtime::get_call_stack);
         call_stack.as_slice()
     }
+
+    fn get_named_key(&self, name: &str) -> Option<Key> {
+        runtime::get_key(name)
+    }
+
+    fn put_named_key(&self, name: &str, key: Key) {
+        runtime::put_key(name, key);
+    }
+
+    fn remove_named_key(&self, name: &str) {
+        runtime::remove_key(name);
+    }
+
+    fn new_uref<T: CLTyped + ToBytes>(&self, value: T) -> URef {
+        storage::new_uref(value)
+    }
+
+    fn read<T: CLTyped + FromBytes>(&self, uref: URef) -> Option<T> {
+        storage::read(uref).unwrap_or_revert()
+    }
+
+    fn write<T: CLTyped + ToBytes>(&self, uref: URef, value: T) {
+        storage::write(uref, value);
+    }
+
+    fn new_dictionary(&self, name: &str) -> URef {
+        storage::new_dictionary(name).unwrap_or_revert()
+    }
+
+    fn dictionary_get<T: CLTyped + FromBytes>(&self, dictionary: URef, key: &str) -> Option<T> {
+        storage::dictionary_get(dictionary, key).unwrap_or_revert()
+    }
+
+    fn dictionary_put<T: CLTyped + ToBytes>(&self, dictionary: URef, key: &str, value: T) {
+        storage::dictionary_put(dictionary, key, value);
+    }
+
+    fn block_time(&self) -> u64 {
+        u64::from(runtime::get_blocktime())
+    }
+
+    fn blake2b(&self, input: &[u8]) -> [u8; 32] {
+        runtime::blake2b(input)
+    }
+
+    fn call_versioned_contract<T: CLTyped + FromBytes>(
+        &self,
+        contract_package_hash: ContractPackageHash,
+        entry_point: &str,
+        args: RuntimeArgs,
+    ) -> T {
+        runtime::call_versioned_contract(contract_package_hash, None, entry_point, args)
+    }
+
+    fn revert(&self, error: ApiError) -> ! {
+        runtime::revert(error)
+    }
 }
@@ -7,8 +7,12 @@ mod admin_control;
 mod contract_context;
 mod contract_storage;
 mod data;
+#[cfg(feature = "mock")]
+mod mock_contract_storage;
 
 pub use admin_control::AdminControl;
 pub use contract_context::ContractContext;
-pub use contract_storage::{ContractStorage, OnChainContractStorage};
+pub use contract_storage::{ContractStorage, OnChainContractStorage, UnwrapOrRevertIn};
 pub use data::{get_key, key_and_value_to_str, key_to_str, set_key, Dict};
+#[cfg(feature = "mock")]
+pub use mock_contract_storage::{ContractCall, MockContractStorage};
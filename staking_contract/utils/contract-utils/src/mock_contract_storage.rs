@@ -0,0 +1,200 @@
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::cell::RefCell;
+
+use blake2::{
+    digest::{Update, VariableOutput},
+    VarBlake2b,
+};
+use casper_types::{
+    bytesrepr::{FromBytes, ToBytes},
+    system::CallStackElement,
+    AccessRights, ApiError, CLTyped, CLValue, ContractPackageHash, Key, RuntimeArgs, URef,
+};
+
+use crate::ContractStorage;
+
+type CallHandler = Box<dyn Fn(&RuntimeArgs) -> CLValue>;
+
+/// A call made to another contract.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractCall {
+    pub contract_package_hash: ContractPackageHash,
+    pub entry_point: String,
+    pub args: RuntimeArgs,
+}
+
+#[derive(Default)]
+struct MockState {
+    named_keys: BTreeMap<String, Key>,
+    values: BTreeMap<[u8; 32], CLValue>,
+    dictionaries: BTreeMap<([u8; 32], String), CLValue>,
+    uref_count: u64,
+    block_time: u64,
+    handlers: BTreeMap<(ContractPackageHash, String), CallHandler>,
+    calls: Vec<ContractCall>,
+}
+
+/// An in-memory [`ContractStorage`], so contracts can be run on the host without wasm.
+///
+/// Reverts panic with the error, so tests can expect them. Calls to other contracts are recorded,
+/// and return what the handler registered with [`on_call`](Self::on_call) returns, or `()`.
+#[derive(Default)]
+pub struct MockContractStorage {
+    call_stack: Vec<CallStackElement>,
+    state: RefCell<MockState>,
+}
+
+impl MockContractStorage {
+    /// Sets the call stack, the last element being the contract itself and the one before it its
+    /// caller.
+    pub fn set_call_stack(&mut self, call_stack: Vec<CallStackElement>) {
+        self.call_stack = call_stack;
+    }
+
+    /// Sets the block time, in milliseconds.
+    pub fn set_block_time(&self, block_time: u64) {
+        self.state.borrow_mut().block_time = block_time;
+    }
+
+    /// Answers the calls to `entry_point` of the given contract with `handler`.
+    pub fn on_call<F>(
+        &self,
+        contract_package_hash: ContractPackageHash,
+        entry_point: &str,
+        handler: F,
+    ) where
+        F: Fn(&RuntimeArgs) -> CLValue + 'static,
+    {
+        self.state.borrow_mut().handlers.insert(
+            (contract_package_hash, entry_point.to_string()),
+            Box::new(handler),
+        );
+    }
+
+    /// Returns the calls made to other contracts so far, in order.
+    pub fn calls(&self) -> Vec<ContractCall> {
+        self.state.borrow().calls.clone()
+    }
+
+    fn value<T: CLTyped + FromBytes>(value: CLValue) -> T {
+        value
+            .into_t()
+            .unwrap_or_else(|error| panic!("stored value has another type: {:?}", error))
+    }
+
+    fn cl_value<T: CLTyped + ToBytes>(value: T) -> CLValue {
+        CLValue::from_t(value)
+            .unwrap_or_else(|error| panic!("value can't be serialized: {:?}", error))
+    }
+}
+
+impl ContractStorage for MockContractStorage {
+    fn call_stack(&self) -> &[CallStackElement] {
+        &self.call_stack
+    }
+
+    fn get_named_key(&self, name: &str) -> Option<Key> {
+        self.state.borrow().named_keys.get(name).copied()
+    }
+
+    fn put_named_key(&self, name: &str, key: Key) {
+        self.state
+            .borrow_mut()
+            .named_keys
+            .insert(name.to_string(), key);
+    }
+
+    fn remove_named_key(&self, name: &str) {
+        self.state.borrow_mut().named_keys.remove(name);
+    }
+
+    fn new_uref<T: CLTyped + ToBytes>(&self, value: T) -> URef {
+        let mut state = self.state.borrow_mut();
+        state.uref_count += 1;
+        let mut address = [0u8; 32];
+        address[..8].copy_from_slice(&state.uref_count.to_be_bytes());
+        state.values.insert(address, Self::cl_value(value));
+        URef::new(address, AccessRights::READ_ADD_WRITE)
+    }
+
+    fn read<T: CLTyped + FromBytes>(&self, uref: URef) -> Option<T> {
+        let value = self.state.borrow().values.get(&uref.addr()).cloned();
+        value.map(Self::value)
+    }
+
+    fn write<T: CLTyped + ToBytes>(&self, uref: URef, value: T) {
+        self.state
+            .borrow_mut()
+            .values
+            .insert(uref.addr(), Self::cl_value(value));
+    }
+
+    fn new_dictionary(&self, name: &str) -> URef {
+        if self.get_named_key(name).is_some() {
+            panic!("named key {} already exists", name);
+        }
+        let uref = self.new_uref(());
+        self.put_named_key(name, Key::from(uref));
+        uref
+    }
+
+    fn dictionary_get<T: CLTyped + FromBytes>(&self, dictionary: URef, key: &str) -> Option<T> {
+        let value = self
+            .state
+            .borrow()
+            .dictionaries
+            .get(&(dictionary.addr(), key.to_string()))
+            .cloned();
+        value.map(Self::value)
+    }
+
+    fn dictionary_put<T: CLTyped + ToBytes>(&self, dictionary: URef, key: &str, value: T) {
+        self.state
+            .borrow_mut()
+            .dictionaries
+            .insert((dictionary.addr(), key.to_string()), Self::cl_value(value));
+    }
+
+    fn block_time(&self) -> u64 {
+        self.state.borrow().block_time
+    }
+
+    fn blake2b(&self, input: &[u8]) -> [u8; 32] {
+        let mut result = [0u8; 32];
+        let mut hasher = VarBlake2b::new(32).expect("should create hasher");
+        hasher.update(input);
+        hasher.finalize_variable(|hash| result.copy_from_slice(hash));
+        result
+    }
+
+    fn call_versioned_contract<T: CLTyped + FromBytes>(
+        &self,
+        contract_package_hash: ContractPackageHash,
+        entry_point: &str,
+        args: RuntimeArgs,
+    ) -> T {
+        let mut state = self.state.borrow_mut();
+        let value = match state
+            .handlers
+            .get(&(contract_package_hash, entry_point.to_string()))
+        {
+            Some(handler) => handler(&args),
+            None => Self::cl_value(()),
+        };
+        state.calls.push(ContractCall {
+            contract_package_hash,
+            entry_point: entry_point.to_string(),
+            args,
+        });
+        Self::value(value)
+    }
+
+    fn revert(&self, error: ApiError) -> ! {
+        panic!("reverted with {:?}", error)
+    }
+}